@@ -20,6 +20,15 @@ fn normal_states(c: &mut Criterion) {
             },
         );
 
+        // Benchmark: solving normal states one payoff at a time
+        group.bench_with_input(
+            BenchmarkId::new("solve_direct", format!("M={},s={}", ruleset.0, ruleset.1)),
+            &ruleset,
+            |b, _| {
+                b.iter(|| solver.solve_normal_states_direct());
+            },
+        );
+
         // Benchmark: find optimal action
         group.bench_with_input(
             BenchmarkId::new(
@@ -23,5 +23,31 @@ fn all_states(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(benches, all_states);
+fn tile_heights(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tile_heights");
+    // Each solve takes seconds, so take fewer samples.
+    group.sample_size(10);
+
+    // Benchmark: complete solve with each tile height (0 = the default)
+    for height in [0, 1, 4, 16, 64, 256] {
+        group.bench_with_input(
+            BenchmarkId::new("solve", format!("M=1000,s=6,h={height}")),
+            &height,
+            |b, &height| {
+                b.iter(|| {
+                    let solver = DpSolver::new(black_box(1000), black_box(6));
+                    let mut solver = match height {
+                        0 => solver,
+                        height => solver.with_tile_height(height),
+                    };
+                    solver.solve();
+                });
+            },
+        );
+    }
+
+    group.finish();
+}
+
+criterion_group!(benches, all_states, tile_heights);
 criterion_main!(benches);
@@ -1,5 +1,6 @@
 use criterion::{BenchmarkId, Criterion, black_box, criterion_group, criterion_main};
 use greed::DpSolver;
+use greed::dp::{PMFLookup, TerminalTable};
 
 fn terminal_states(c: &mut Criterion) {
     let mut group = c.benchmark_group("terminal_states");
@@ -11,12 +12,13 @@ fn terminal_states(c: &mut Criterion) {
         let mut solver = DpSolver::new(ruleset.0, ruleset.1);
         solver.precompute_pmfs();
 
-        // Benchmark: solving normal states
+        // Benchmark: solving terminal states
+        let pmfs: PMFLookup = PMFLookup::precompute(ruleset.0, ruleset.1);
         group.bench_with_input(
             BenchmarkId::new("solve", format!("M={},s={}", ruleset.0, ruleset.1)),
             &ruleset,
             |b, _| {
-                b.iter(|| solver.solve_terminal_states());
+                b.iter(|| TerminalTable::solve(black_box(ruleset.0), &pmfs));
             },
         );
 
@@ -47,6 +49,23 @@ fn terminal_states(c: &mut Criterion) {
                 });
             },
         );
+
+        // Benchmark: computing an optimal payoff by summing every dice total
+        group.bench_with_input(
+            BenchmarkId::new(
+                "calc_payoff_direct",
+                format!("M={},s={}", ruleset.0, ruleset.1),
+            ),
+            &ruleset,
+            |b, _| {
+                b.iter(|| {
+                    solver.calc_terminal_payoff_direct(
+                        black_box(greed::State::new(ruleset.0 / 2, ruleset.1 / 2, false)),
+                        3,
+                    )
+                });
+            },
+        );
     }
 
     group.finish();
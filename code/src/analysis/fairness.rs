@@ -0,0 +1,111 @@
+//! Measure the first-mover advantage and balance it with a handicap.
+
+use crate::{DpSolver, Policy, Ruleset, Solver};
+
+/// The first-mover advantage of a ruleset and the komi that best offsets it.
+///
+/// The komi is a head start given to the second player. The balanced ruleset
+/// is the one whose komi brings the first player's payoff closest to zero,
+/// i.e. closest to an even game.
+#[derive(Debug, Copy, Clone)]
+pub struct Fairness {
+    /// The ruleset being analyzed (without a komi).
+    ruleset: Ruleset,
+    /// The first player's payoff from the standard initial state.
+    advantage: f64,
+    /// The ruleset with the komi that best balances the game.
+    balanced: Ruleset,
+    /// The first player's payoff under the balanced ruleset.
+    balanced_advantage: f64,
+}
+
+impl Fairness {
+    /// Compute the first-mover advantage and balancing komi from a solved
+    /// policy.
+    ///
+    /// Since the policy covers every state, the payoff of each candidate komi
+    /// `k` is read directly from the state `(0, k, normal)`. Ties are broken
+    /// in favor of the smaller komi.
+    #[must_use]
+    pub fn new(ruleset: Ruleset, policy: &Policy) -> Self {
+        let ruleset = ruleset.with_komi(0);
+        let advantage = policy.get(&ruleset.initial_state()).payoff();
+
+        let (komi, balanced_advantage) = (0..=ruleset.max())
+            .map(|komi| {
+                let state = ruleset.with_komi(komi).initial_state();
+                (komi, policy.get(&state).payoff())
+            })
+            .min_by(|a, b| a.1.abs().total_cmp(&b.1.abs()))
+            .unwrap();
+
+        Self {
+            ruleset,
+            advantage,
+            balanced: ruleset.with_komi(komi),
+            balanced_advantage,
+        }
+    }
+    /// Get the ruleset being analyzed.
+    #[must_use]
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+    /// Get the first player's payoff from the standard initial state.
+    ///
+    /// This is P(first player wins) - P(first player loses) under optimal
+    /// play by both players.
+    #[must_use]
+    pub fn advantage(&self) -> f64 {
+        self.advantage
+    }
+    /// Get the ruleset with the komi that best balances the game.
+    #[must_use]
+    pub fn balanced(&self) -> Ruleset {
+        self.balanced
+    }
+    /// Get the first player's payoff under the balanced ruleset.
+    #[must_use]
+    pub fn balanced_advantage(&self) -> f64 {
+        self.balanced_advantage
+    }
+}
+
+/// Solve a ruleset and compute its fairness report.
+#[must_use]
+pub fn fairness(ruleset: Ruleset) -> Fairness {
+    let policy = DpSolver::from_ruleset(ruleset).policy();
+    Fairness::new(ruleset, &policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::State;
+
+    #[test]
+    fn test_balanced_komi_is_no_worse() {
+        let report = fairness(Ruleset::new(30, 6));
+
+        assert!((report.advantage() - 0.022667).abs() < 1e-6);
+        assert_eq!(report.balanced().komi(), 14);
+        assert!((report.balanced_advantage() + 0.000405).abs() < 1e-6);
+        assert_eq!(report.ruleset().komi(), 0);
+
+        // A komi past the maximum would start the second player bust.
+        assert!(Ruleset::new(30, 6).try_with_komi(30).is_ok());
+        assert!(Ruleset::new(30, 6).try_with_komi(31).is_err());
+    }
+
+    #[test]
+    fn test_advantage_matches_policy() {
+        let policy = DpSolver::new(20, 4).policy();
+        let report = Fairness::new(Ruleset::new(20, 4).with_komi(3), &policy);
+
+        let payoff = policy.get(&State::new(0, 0, false)).payoff();
+        assert!((report.advantage() - payoff).abs() < 1e-12);
+        let komi = report.balanced().komi();
+        let payoff = policy.get(&State::new(0, komi, false)).payoff();
+        assert!((report.balanced_advantage() - payoff).abs() < 1e-12);
+    }
+}
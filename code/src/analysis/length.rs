@@ -0,0 +1,166 @@
+//! Exact distribution of the number of turns a game lasts.
+
+use super::markov::Chain;
+use crate::{Policy, Ruleset, State};
+
+/// The exact distribution of game length, measured in turns.
+///
+/// Every roll and every stand counts as one turn. The distribution is computed
+/// by propagating the state distribution forward through the Markov chain
+/// induced by the two policies, so it carries no sampling error. (With a soft
+/// `BustRule` a game can last forever, so the distribution stops once almost
+/// every game has ended.)
+#[derive(Debug, Clone)]
+pub struct GameLength {
+    /// `pmf[k]` is the probability that the game lasts exactly `k` turns.
+    pmf: Vec<f64>,
+}
+
+impl GameLength {
+    /// Compute the game length distribution from `start` when the first
+    /// player (to move in `start`) follows `policies.0` and the second
+    /// follows `policies.1`.
+    #[must_use]
+    pub fn new(ruleset: Ruleset, start: State, policies: (&Policy, &Policy)) -> Self {
+        let mut pmf = vec![0.0];
+        Chain::new(ruleset, policies).run(start, |_, _, ended| pmf.push(ended.iter().sum()));
+        Self { pmf }
+    }
+    /// Get the probability that the game lasts exactly `turns` turns.
+    #[must_use]
+    pub fn probability(&self, turns: usize) -> f64 {
+        self.pmf.get(turns).copied().unwrap_or(0.0)
+    }
+    /// Get the full distribution, indexed by number of turns.
+    #[must_use]
+    pub fn pmf(&self) -> &[f64] {
+        &self.pmf
+    }
+    /// Get the expected number of turns.
+    #[must_use]
+    pub fn mean(&self) -> f64 {
+        self.pmf
+            .iter()
+            .enumerate()
+            .map(|(turns, p)| turns as f64 * p)
+            .sum()
+    }
+    /// Get the smallest number of turns within which the game ends with at
+    /// least probability `q`.
+    #[must_use]
+    pub fn quantile(&self, q: f64) -> usize {
+        let mut cumulative = 0.0;
+        for (turns, p) in self.pmf.iter().enumerate() {
+            cumulative += p;
+            if cumulative >= q {
+                return turns;
+            }
+        }
+        self.pmf.len() - 1
+    }
+    /// Export the distribution to a CSV file.
+    ///
+    /// Creates a CSV with columns: turns, probability
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written to.
+    pub fn csv(&self, path: &str) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_path(path)?;
+
+        // Write headers
+        writer.serialize(("turns", "probability"))?;
+        for (turns, probability) in self.pmf.iter().enumerate() {
+            writer.serialize((turns, probability))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::analysis::Reachability;
+    use crate::{BustRule, DpSolver, EndRule, Solver};
+
+    #[test]
+    fn test_length_distribution_sums_to_one() {
+        let policy = DpSolver::new(30, 6).policy();
+        let ruleset = Ruleset::new(30, 6);
+        let length = GameLength::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+
+        let total: f64 = length.pmf().iter().sum();
+        assert!(
+            (total - 1.0).abs() < 1e-10,
+            "Lengths should sum to 1: {total}"
+        );
+        assert_eq!(length.probability(0), 0.0, "A game lasts at least one turn");
+        // The longest game takes 27 turns, and most take 6.
+        assert_eq!(length.pmf().len(), 28);
+        for (turns, p) in [
+            (1, 0.009902263374485),
+            (4, 0.152200277665805),
+            (6, 0.292514380734141),
+        ] {
+            assert!((length.probability(turns) - p).abs() < 1e-12, "{turns}");
+        }
+    }
+
+    #[test]
+    fn test_every_end_rule_ends() {
+        for end_rule in EndRule::ALL {
+            let ruleset = Ruleset::new(20, 4).with_end_rule(end_rule);
+            let policy = DpSolver::from_ruleset(ruleset).policy();
+            let length = GameLength::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+
+            let total: f64 = length.pmf().iter().sum();
+            assert!((total - 1.0).abs() < 1e-10, "{end_rule}: {total}");
+            // Every other turn (at least) is a roll.
+            assert!(length.pmf().len() <= 4 * 20 + 4, "{end_rule}");
+        }
+    }
+
+    #[test]
+    fn test_soft_busts_end_almost_surely() {
+        for bust_rule in [BustRule::Reset, BustRule::Revert, BustRule::Bounce] {
+            let ruleset = Ruleset::new(20, 4).with_bust_rule(bust_rule);
+            let policy = DpSolver::from_ruleset(ruleset).policy();
+            let length = GameLength::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+
+            let total: f64 = length.pmf().iter().sum();
+            assert!((total - 1.0).abs() < 1e-10, "{bust_rule}: {total}");
+        }
+    }
+
+    #[test]
+    fn test_exact_win_ends_the_game() {
+        let ruleset = Ruleset::new(20, 4).with_exact_win(true);
+        let policy = DpSolver::from_ruleset(ruleset).policy();
+        let length = GameLength::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+        let total: f64 = length.pmf().iter().sum();
+        assert!((total - 1.0).abs() < 1e-10, "{total}");
+
+        // Nobody ever moves with a score on the maximum.
+        let reach = Reachability::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+        assert!(
+            reach
+                .iter()
+                .filter(|(s, _)| s.active() == 20 || s.queued() == 20)
+                .all(|(_, p)| p == 0.0)
+        );
+    }
+
+    #[test]
+    fn test_terminal_start_lasts_one_turn() {
+        let policy = DpSolver::new(10, 2).policy();
+        let length = GameLength::new(
+            Ruleset::new(10, 2),
+            State::new(3, 7, true),
+            (&policy, &policy),
+        );
+
+        assert!((length.probability(1) - 1.0).abs() < 1e-12);
+        assert!((length.mean() - 1.0).abs() < 1e-12);
+    }
+}
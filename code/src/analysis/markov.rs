@@ -0,0 +1,177 @@
+//! Forward propagation of state distributions under a pair of policies.
+
+use std::cmp::Ordering;
+
+use crate::dp::PMFLookup;
+use crate::{BustRule, EndRule, Policy, Ruleset, State};
+
+/// With a soft `BustRule`, a game can last forever, so propagation stops once
+/// less than this much probability is still playing.
+const TAIL: f64 = 1e-12;
+
+/// The Markov chain induced by two players following fixed policies.
+///
+/// Distributions are dense vectors laid out like the policy table, so a
+/// state's probability lives at `Policy::index(state)`. Turn `k` is played by
+/// the first player's policy when `k` is even and the second player's when `k`
+/// is odd.
+pub(crate) struct Chain<'a> {
+    /// Game configuration (maximum score and die sides).
+    ruleset: Ruleset,
+    /// The policies of the first and second player.
+    policies: [&'a Policy; 2],
+    /// Precomputed probability mass functions for dice rolls.
+    pmfs: PMFLookup,
+}
+
+impl<'a> Chain<'a> {
+    /// Create the chain for a ruleset and a (first, second) policy pair.
+    pub(crate) fn new(ruleset: Ruleset, policies: (&'a Policy, &'a Policy)) -> Self {
+        Self {
+            ruleset,
+            policies: [policies.0, policies.1],
+            pmfs: PMFLookup::precompute(ruleset.max(), ruleset.sides()),
+        }
+    }
+    /// Propagate the game forward from `start` until every game has ended.
+    ///
+    /// Before each turn `k` is played, `visit(k, dist, ended)` is called with
+    /// the distribution of states at the start of that turn and the
+    /// probabilities that the game ends during it with a win for the first
+    /// player, a draw, or a win for the second player. The game always ends, since
+    /// every roll strictly increases the combined score and a game ends by the
+    /// second of two consecutive stands.
+    ///
+    /// With a soft `BustRule`, busts lower scores and a game can go on
+    /// indefinitely, so propagation stops once less than `TAIL` of the
+    /// probability is still playing.
+    pub(crate) fn run(&self, start: State, mut visit: impl FnMut(usize, &[f64], [f64; 3])) {
+        let layout = self.policies[0];
+        let mut dist = vec![0.0; layout.len()];
+        dist[layout.index(&start)] = 1.0;
+
+        for turn in 0.. {
+            let policy = self.policies[turn % 2];
+            let mut next = vec![0.0; dist.len()];
+            let mut ended = [0.0; 3];
+            let mut alive = false;
+            let mut playing = 0.0;
+
+            for (index, &probability) in dist.iter().enumerate() {
+                if probability == 0.0 {
+                    continue;
+                }
+                let state = layout.state(index);
+                let n = policy.get(&state).n();
+                let outcome = self.step(state, n, probability, |state, p| {
+                    next[layout.index(&state)] += p;
+                    alive = true;
+                    playing += p;
+                });
+                for (ended, p) in ended.iter_mut().zip(outcome) {
+                    *ended += p;
+                }
+            }
+            // The outcome is the mover's, and the second player moves on odd turns.
+            if turn % 2 == 1 {
+                ended.reverse();
+            }
+
+            visit(turn, &dist, ended);
+            let soft = self.ruleset.bust_rule() != BustRule::Lose;
+            if !alive || (soft && playing < TAIL) {
+                break;
+            }
+            dist = next;
+        }
+    }
+    /// Play `n` dice from `state`, which is occupied with `probability`.
+    ///
+    /// Calls `emit(next_state, p)` for every state the game continues in and
+    /// returns the probability mass with which the game ends in a win, a draw
+    /// and a loss for the mover.
+    fn step(
+        &self,
+        state: State,
+        n: u32,
+        probability: f64,
+        mut emit: impl FnMut(State, f64),
+    ) -> [f64; 3] {
+        let max = self.ruleset.max();
+        let end_rule = self.ruleset.end_rule();
+        if state.last() && end_rule == EndRule::LastTurn {
+            return self.final_roll(state, n, probability);
+        }
+        if n == 0 {
+            if state.last() || end_rule == EndRule::Immediate {
+                return compare(state.active(), state.queued(), probability);
+            }
+            emit(
+                State::new(state.queued(), state.active(), true),
+                probability,
+            );
+            return [0.0; 3];
+        }
+        // Landing exactly on the maximum may end the game on the spot.
+        let mut won = 0.0;
+        let mut land = |total: u32, p: f64| {
+            if self.ruleset.exact_win() && state.active() + total == max {
+                won += p;
+                return;
+            }
+            let score = self.ruleset.score_after(state.active(), total);
+            let score = score.expect("a soft bust always lands on a score");
+            emit(State::new(state.queued(), score, false), p);
+        };
+        if self.ruleset.bust_rule() != BustRule::Lose {
+            // Every total lands somewhere.
+            for total in n..=self.ruleset.sides() * n {
+                land(total, probability * self.pmfs.lookup(n, total));
+            }
+            return [won, 0.0, 0.0];
+        }
+        if state.active() + n > max {
+            // Even the smallest total busts.
+            return [0.0, 0.0, probability];
+        }
+
+        let headroom = max - state.active();
+        for total in n..=(self.ruleset.sides() * n).min(headroom) {
+            land(total, probability * self.pmfs.lookup(n, total));
+        }
+        // Every total past the headroom busts.
+        [won, 0.0, probability * (1.0 - self.pmfs.cdf(n, headroom))]
+    }
+    /// Play `n` dice in the final turn of `EndRule::LastTurn` from `state`,
+    /// which is occupied with `probability`, returning the probability mass
+    /// of a win, a draw and a loss for the mover.
+    fn final_roll(&self, state: State, n: u32, probability: f64) -> [f64; 3] {
+        if n == 0 {
+            return compare(state.active(), state.queued(), probability);
+        }
+        let mut outcome = [0.0; 3];
+        for total in n..=self.ruleset.sides() * n {
+            let p = probability * self.pmfs.lookup(n, total);
+            let exact = self.ruleset.exact_win() && state.active() + total == self.ruleset.max();
+            let landed = match self.ruleset.score_after(state.active(), total) {
+                _ if exact => [p, 0.0, 0.0],
+                Some(score) => compare(score, state.queued(), p),
+                None => [0.0, 0.0, p],
+            };
+            for (outcome, p) in outcome.iter_mut().zip(landed) {
+                *outcome += p;
+            }
+        }
+        outcome
+    }
+}
+
+/// Split `probability` into a win, a draw and a loss for a player ending the
+/// game on `score` against `other`.
+fn compare(score: u32, other: u32, probability: f64) -> [f64; 3] {
+    match score.cmp(&other) {
+        Ordering::Greater => [probability, 0.0, 0.0],
+        Ordering::Equal => [0.0, probability, 0.0],
+        Ordering::Less => [0.0, 0.0, probability],
+    }
+}
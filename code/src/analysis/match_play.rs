@@ -0,0 +1,471 @@
+//! Match play: the first player to win `k` games wins the match.
+
+use std::fmt;
+
+use super::markov::Chain;
+use crate::{
+    BustRule, DpSolver, EndRule, HandicapPolicy, HandicapSolver, Policy, Ruleset, Seat, Solver,
+    TieRule, UnsupportedRuleset,
+};
+
+/// Fixed-point iteration stops once no match payoff moves by more than this.
+const TOLERANCE: f64 = 1e-12;
+/// The most rounds of fixed-point iteration for one pair of scores.
+const MAX_ITERATIONS: usize = 100;
+
+/// A match score, from the perspective of the player who starts the next
+/// game.
+///
+/// Starts alternate from game to game (drawn games included), so who starts
+/// is implicit in the perspective, as whose turn it is is for a `State`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct MatchState {
+    /// Games won by the player who starts the next game.
+    wins: u32,
+    /// Games won by the other player.
+    losses: u32,
+}
+
+impl MatchState {
+    /// Create a match score from the perspective of the next game's starter.
+    #[must_use]
+    pub fn new(wins: u32, losses: u32) -> Self {
+        Self { wins, losses }
+    }
+    /// Get the games won by the player who starts the next game.
+    #[must_use]
+    pub fn wins(&self) -> u32 {
+        self.wins
+    }
+    /// Get the games won by the other player.
+    #[must_use]
+    pub fn losses(&self) -> u32 {
+        self.losses
+    }
+    /// The score after the starter wins (`Some(true)`), loses
+    /// (`Some(false)`) or draws (`None`) the next game, from the perspective
+    /// of the player who starts the game after it.
+    #[must_use]
+    pub fn after(&self, won: Option<bool>) -> Self {
+        match won {
+            Some(true) => Self::new(self.losses, self.wins + 1),
+            Some(false) => Self::new(self.losses + 1, self.wins),
+            None => Self::new(self.losses, self.wins),
+        }
+    }
+}
+
+/// The error returned when first-to-`k` matches cannot be solved.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum MatchError {
+    /// The ruleset (or the target) is not supported (see `MatchPlay::try_new`).
+    Unsupported(UnsupportedRuleset),
+    /// Every game is drawn under single-game play, so no match ever ends.
+    NeverDecided,
+    /// Winning the game at this score is worth no more than losing it.
+    Degenerate(MatchState),
+    /// The payoffs of this score and its mirror image did not converge within
+    /// `MAX_ITERATIONS` rounds.
+    NotConverged(MatchState),
+}
+
+impl fmt::Display for MatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Unsupported(error) => write!(f, "{error}"),
+            Self::NeverDecided => f.write_str("every game is drawn, so no match ever ends"),
+            Self::Degenerate(state) => write!(
+                f,
+                "winning the game at {}-{} is worth no more than losing it",
+                state.wins, state.losses
+            ),
+            Self::NotConverged(state) => write!(
+                f,
+                "match payoffs of {}-{} did not converge in {MAX_ITERATIONS} iterations",
+                state.wins, state.losses
+            ),
+        }
+    }
+}
+
+impl std::error::Error for MatchError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Unsupported(error) => Some(error),
+            Self::NeverDecided | Self::Degenerate(_) | Self::NotConverged(_) => None,
+        }
+    }
+}
+
+/// What a game in a match state is played for, and how it is played.
+#[derive(Debug, Clone)]
+struct Game {
+    /// The starter's match payoff under match-aware play.
+    payoff: f64,
+    /// The starter's match payoff when every game is played for itself.
+    single_game_payoff: f64,
+    /// What a drawn game is worth to the starter, between a lost game (-1)
+    /// and a won one (1).
+    tie_payoff: f64,
+    /// The match-aware policy, with the starter in `Seat::First`.
+    policy: HandicapPolicy,
+    /// The number of decisions (of either seat) that differ from the
+    /// single-game policy.
+    changed: usize,
+}
+
+/// A match-level solver for first-to-`target` matches.
+///
+/// Each game is worth P(win) - P(lose) of itself, but to a match only the
+/// score it leaves matters. A won game is worth `a` to the starter, a lost one
+/// `b`, and a drawn one `d` (the game is replayed with the other player
+/// starting), all read from the match states after it. The starter's match
+/// payoff is then an affine function of a game payoff in which a tie is worth
+/// `(2d - a - b) / (a - b)` to the first seat and its negation to the
+/// second, which `HandicapSolver` solves.
+///
+/// So only draws separate match play from single-game play: without them,
+/// winning the game is all that counts, whatever the score. A drawn game leads
+/// back to the mirrored score, so each pair of mirrored scores is solved
+/// together by fixed-point iteration, by decreasing number of games won.
+///
+/// For comparison, the match is also played with the single-game policy from
+/// `DpSolver` in every game, whose per-game win, draw and loss probabilities
+/// give each score's match payoff directly.
+#[derive(Debug, Clone)]
+pub struct MatchPlay {
+    /// The ruleset of every game.
+    ruleset: Ruleset,
+    /// The number of games needed to win the match.
+    target: u32,
+    /// The starter's probabilities of winning, drawing and losing a game
+    /// under single-game play.
+    outcomes: [f64; 3],
+    /// The game of each unfinished score, at `wins * target + losses`.
+    games: Vec<Game>,
+}
+
+impl MatchPlay {
+    /// Solve first-to-`target` matches of `ruleset`, given its solved
+    /// single-game policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the matches cannot be solved (see `try_new`).
+    #[must_use]
+    pub fn new(ruleset: Ruleset, target: u32, single_game: &Policy) -> Self {
+        Self::try_new(ruleset, target, single_game).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Solve first-to-`target` matches of `ruleset`, given its solved
+    /// single-game policy, if they can be.
+    ///
+    /// # Errors
+    ///
+    /// Returns `MatchError::Unsupported` if `target` is 0, or if the ruleset
+    /// does not use `TieRule::Draw` (the other rules decide every game, so
+    /// single-game play is already optimal), `BustRule::Lose` (as for
+    /// `HandicapSolver`), or if it uses `EndRule::BothStand` (under which
+    /// single-game play draws every game). Returns
+    /// `MatchError::NeverDecided` if every game is drawn anyway, and
+    /// `MatchError::Degenerate` or `MatchError::NotConverged` if the payoffs
+    /// of a pair of scores cannot be solved.
+    pub fn try_new(
+        ruleset: Ruleset,
+        target: u32,
+        single_game: &Policy,
+    ) -> Result<Self, MatchError> {
+        let unsupported = |reason| Err(MatchError::Unsupported(UnsupportedRuleset { reason }));
+        if target == 0 {
+            return unsupported("a match needs at least one game");
+        }
+        if ruleset.tie_rule() != TieRule::Draw {
+            return unsupported("match play is only solved for drawn ties");
+        }
+        if ruleset.bust_rule() != BustRule::Lose {
+            return unsupported("match play must use BustRule::Lose");
+        }
+        if ruleset.end_rule() == EndRule::BothStand {
+            return unsupported("with EndRule::BothStand every game is drawn");
+        }
+        let mut outcomes = [0.0; 3];
+        Chain::new(ruleset, (single_game, single_game)).run(
+            ruleset.initial_state(),
+            |_, _, ended| {
+                for (outcome, p) in outcomes.iter_mut().zip(ended) {
+                    *outcome += p;
+                }
+            },
+        );
+        // A drawn game is replayed, so games that are never decided never
+        // end the match.
+        let [win, _, lose] = outcomes;
+        if win + lose <= TOLERANCE {
+            return Err(MatchError::NeverDecided);
+        }
+        // Scores are filled in by decreasing number of games won, so slots
+        // are placeholders until then.
+        let placeholder = Game {
+            payoff: 0.0,
+            single_game_payoff: 0.0,
+            tie_payoff: 0.0,
+            policy: HandicapPolicy::new(0),
+            changed: 0,
+        };
+        let mut play = Self {
+            ruleset,
+            target,
+            outcomes,
+            games: vec![placeholder; (target * target) as usize],
+        };
+        for played in (0..=2 * (target - 1)).rev() {
+            for wins in played.saturating_sub(target - 1)..=played.min(target - 1) {
+                let losses = played - wins;
+                if wins <= losses {
+                    play.solve_pair(single_game, MatchState::new(wins, losses))?;
+                }
+            }
+        }
+        Ok(play)
+    }
+    /// Solve `state` and its mirror image, which a drawn game leads to.
+    fn solve_pair(&mut self, single_game: &Policy, state: MatchState) -> Result<(), MatchError> {
+        let mirror = state.after(None);
+        let pair: &[MatchState] = match state == mirror {
+            true => &[state],
+            false => &[state, mirror],
+        };
+
+        // Playing for the game, a drawn game leads back to the pair, so
+        // `x = c_x - draw * y` and `y = c_y - draw * x`.
+        let [win, draw, lose] = self.outcomes;
+        let constant = |state: MatchState| {
+            win * -self.payoff(state.after(Some(true)))
+                + lose * -self.payoff(state.after(Some(false)))
+        };
+        let (c_x, c_y) = (constant(state), constant(mirror));
+        let single_game_payoffs = [
+            (c_x - draw * c_y) / (1.0 - draw * draw),
+            (c_y - draw * c_x) / (1.0 - draw * draw),
+        ];
+
+        let mut payoffs = single_game_payoffs;
+        for _ in 0..MAX_ITERATIONS {
+            let previous = payoffs;
+            let games = pair
+                .iter()
+                .zip(&single_game_payoffs)
+                .enumerate()
+                .map(|(i, (&state, &single_game_payoff))| {
+                    // A draw hands the mirrored score to the opponent.
+                    let tied = -payoffs[1 - i];
+                    let mut game = self.solve_game(state, tied, single_game)?;
+                    game.single_game_payoff = single_game_payoff;
+                    payoffs[i] = game.payoff;
+                    Ok(game)
+                })
+                .collect::<Result<Vec<Game>, MatchError>>()?;
+            if pair.len() == 1 {
+                payoffs[1] = payoffs[0];
+            }
+            let moved = previous
+                .iter()
+                .zip(&payoffs)
+                .any(|(a, b)| (a - b).abs() > TOLERANCE);
+            if !moved {
+                for (&state, game) in pair.iter().zip(games) {
+                    let index = self.index(state);
+                    self.games[index] = game;
+                }
+                return Ok(());
+            }
+        }
+        Err(MatchError::NotConverged(state))
+    }
+    /// Solve the game played in `state` when a draw is worth `tied` to the
+    /// starter's match payoff.
+    fn solve_game(
+        &self,
+        state: MatchState,
+        tied: f64,
+        single_game: &Policy,
+    ) -> Result<Game, MatchError> {
+        let won = -self.payoff(state.after(Some(true)));
+        let lost = -self.payoff(state.after(Some(false)));
+        let (mid, half) = ((won + lost) / 2.0, (won - lost) / 2.0);
+        // Winning a game always brings the starter closer to the match.
+        if half <= 0.0 {
+            return Err(MatchError::Degenerate(state));
+        }
+        let tie_payoff = (tied - mid) / half;
+        let policy = HandicapSolver::new(self.ruleset)
+            .with_tie_payoff(tie_payoff)
+            .policy();
+        let changed = Seat::ALL
+            .iter()
+            .map(|&seat| {
+                single_game
+                    .iter()
+                    .filter(|(state, action)| policy.seat(seat).get(state).n() != action.n())
+                    .count()
+            })
+            .sum();
+        let payoff = policy.get(&self.ruleset.initial_state()).payoff();
+        Ok(Game {
+            payoff: mid + half * payoff,
+            single_game_payoff: 0.0,
+            tie_payoff,
+            policy,
+            changed,
+        })
+    }
+    /// The index of an unfinished score in `games`.
+    fn index(&self, state: MatchState) -> usize {
+        (state.wins * self.target + state.losses) as usize
+    }
+    /// Get the game of an unfinished score.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the match is over.
+    fn game(&self, state: MatchState) -> &Game {
+        assert!(
+            state.wins < self.target && state.losses < self.target,
+            "the match is over"
+        );
+        &self.games[self.index(state)]
+    }
+    /// Get the ruleset of every game.
+    #[must_use]
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+    /// Get the number of games needed to win the match.
+    #[must_use]
+    pub fn target(&self) -> u32 {
+        self.target
+    }
+    /// Get the starter's probabilities of winning, drawing and losing a game
+    /// when it is played for itself.
+    #[must_use]
+    pub fn outcomes(&self) -> [f64; 3] {
+        self.outcomes
+    }
+    /// Get the match payoff (P(win match) - P(lose match)) of the player who
+    /// starts the next game, under match-aware play.
+    #[must_use]
+    pub fn payoff(&self, state: MatchState) -> f64 {
+        if state.wins == self.target {
+            1.0
+        } else if state.losses == self.target {
+            -1.0
+        } else {
+            self.game(state).payoff
+        }
+    }
+    /// Get the starter's match payoff when every game is played for itself
+    /// (with the single-game policy).
+    #[must_use]
+    pub fn single_game_payoff(&self, state: MatchState) -> f64 {
+        if state.wins == self.target || state.losses == self.target {
+            return self.payoff(state);
+        }
+        self.game(state).single_game_payoff
+    }
+    /// Get what a drawn game is worth to the starter in `state`, on the
+    /// scale of a single game (a lost game is -1 and a won one 1).
+    #[must_use]
+    pub fn tie_payoff(&self, state: MatchState) -> f64 {
+        self.game(state).tie_payoff
+    }
+    /// Get the match-aware policy of the game played in `state`, with the
+    /// starter in `Seat::First`. Its payoffs are on the scale of a single
+    /// game (see `tie_payoff`).
+    #[must_use]
+    pub fn policy(&self, state: MatchState) -> &HandicapPolicy {
+        &self.game(state).policy
+    }
+    /// Get the number of decisions (of either seat) in `state` that differ
+    /// from single-game play.
+    #[must_use]
+    pub fn changed(&self, state: MatchState) -> usize {
+        self.game(state).changed
+    }
+    /// Iterate over every unfinished score.
+    pub fn states(&self) -> impl Iterator<Item = MatchState> + '_ {
+        (0..self.target)
+            .flat_map(move |wins| (0..self.target).map(move |losses| MatchState::new(wins, losses)))
+    }
+}
+
+/// Solve a ruleset and then first-to-`target` matches of it.
+///
+/// # Errors
+///
+/// Returns an error if the matches cannot be solved (see
+/// `MatchPlay::try_new`).
+pub fn match_play(ruleset: Ruleset, target: u32) -> Result<MatchPlay, MatchError> {
+    let policy = DpSolver::from_ruleset(ruleset).policy();
+    MatchPlay::try_new(ruleset, target, &policy)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_outcomes_match_payoff() {
+        for end_rule in [EndRule::LastTurn, EndRule::Immediate] {
+            let ruleset = Ruleset::new(20, 4).with_end_rule(end_rule);
+            let policy = DpSolver::from_ruleset(ruleset).policy();
+            let [win, draw, lose] = MatchPlay::new(ruleset, 1, &policy).outcomes();
+            let payoff = policy.get(&ruleset.initial_state()).payoff();
+            assert!((win - lose - payoff).abs() < 1e-12, "{end_rule}");
+            assert!((win + draw + lose - 1.0).abs() < 1e-12, "{end_rule}");
+            assert!(draw > 0.0, "{end_rule}");
+        }
+    }
+
+    #[test]
+    fn test_match_payoffs() {
+        let play = match_play(Ruleset::new(20, 4), 3).unwrap();
+        for state in play.states() {
+            // The payoff is the game's, rescaled by what each result leaves.
+            let won = -play.payoff(state.after(Some(true)));
+            let lost = -play.payoff(state.after(Some(false)));
+            let game = play.policy(state).get(&play.ruleset().initial_state());
+            let payoff = (won + lost) / 2.0 + (won - lost) / 2.0 * game.payoff();
+            assert!((play.payoff(state) - payoff).abs() < 1e-9, "{state:?}");
+            assert!((-1.0..=1.0).contains(&play.tie_payoff(state)), "{state:?}");
+        }
+        assert!(play.payoff(MatchState::new(2, 0)) > play.payoff(MatchState::new(1, 0)));
+        assert!(play.states().any(|state| play.changed(state) > 0));
+
+        // The deciding game is a first-to-1 match, where a draw hands the
+        // start (and its advantage) to the opponent.
+        let decider = MatchState::new(2, 2);
+        let sudden = match_play(Ruleset::new(20, 4), 1).unwrap();
+        let start = MatchState::new(0, 0);
+        assert!((play.payoff(decider) - sudden.payoff(start)).abs() < 1e-12);
+        assert!((play.tie_payoff(decider) + play.payoff(decider)).abs() < 1e-12);
+        assert!(play.tie_payoff(decider) < 0.0);
+    }
+
+    #[test]
+    fn test_unsolvable_matches() {
+        let ruleset = Ruleset::new(20, 4);
+        let policy = DpSolver::from_ruleset(ruleset).policy();
+        let unsupported = |error| matches!(error, Err(MatchError::Unsupported(_)));
+        assert!(unsupported(MatchPlay::try_new(ruleset, 0, &policy)));
+        let both_stand = ruleset.with_end_rule(EndRule::BothStand);
+        assert!(unsupported(match_play(both_stand, 4)));
+        assert!(unsupported(match_play(
+            ruleset.with_tie_rule(TieRule::Stander),
+            4
+        )));
+
+        // With a coin for a die and 1 to play for, rolling wins or busts
+        // evenly, so both players stand and every game is drawn.
+        let coin = Ruleset::new(1, 2);
+        assert!(matches!(match_play(coin, 2), Err(MatchError::NeverDecided)));
+    }
+}
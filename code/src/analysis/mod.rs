@@ -0,0 +1,18 @@
+//! Analyses built on top of solved policies.
+
+mod fairness;
+mod length;
+mod markov;
+mod match_play;
+mod precision;
+mod push;
+mod reach;
+mod sweep;
+
+pub use fairness::{Fairness, fairness};
+pub use length::GameLength;
+pub use match_play::{MatchError, MatchPlay, MatchState, match_play};
+pub use precision::PrecisionDiff;
+pub use push::{PushValue, push_value};
+pub use reach::Reachability;
+pub use sweep::{SweepRow, sweep};
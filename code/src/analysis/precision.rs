@@ -0,0 +1,118 @@
+//! Compare policies solved with different numeric precisions.
+
+use crate::{Policy, Scalar};
+
+/// How much two policies of the same ruleset disagree.
+///
+/// Typically one policy is solved in a lower precision (e.g. `f32`) and the
+/// other in a higher one (e.g. `DoubleDouble`), so the differences measure
+/// how many decisions are only made "right" by the extra precision.
+#[derive(Debug, Copy, Clone)]
+pub struct PrecisionDiff {
+    /// The number of states in each policy.
+    states: usize,
+    /// The number of normal states whose dice count differs.
+    normal: usize,
+    /// The number of terminal states whose dice count differs.
+    terminal: usize,
+    /// The largest absolute difference between payoffs.
+    max_payoff_difference: f64,
+}
+
+impl PrecisionDiff {
+    /// Compare two policies state by state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the policies do not cover the same states.
+    #[must_use]
+    pub fn new<A: Scalar, B: Scalar>(a: &Policy<A>, b: &Policy<B>) -> Self {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "policies must be for the same maximum score"
+        );
+
+        let mut diff = Self {
+            states: a.len(),
+            normal: 0,
+            terminal: 0,
+            max_payoff_difference: 0.0,
+        };
+        for ((state, x), (_, y)) in a.iter().zip(b.iter()) {
+            if x.n() != y.n() {
+                match state.last() {
+                    true => diff.terminal += 1,
+                    false => diff.normal += 1,
+                }
+            }
+            let difference = (x.payoff().to_f64() - y.payoff().to_f64()).abs();
+            diff.max_payoff_difference = diff.max_payoff_difference.max(difference);
+        }
+        diff
+    }
+    /// Get the number of states in each policy.
+    #[must_use]
+    pub fn states(&self) -> usize {
+        self.states
+    }
+    /// Get the number of states whose dice count differs.
+    #[must_use]
+    pub fn decisions(&self) -> usize {
+        self.normal + self.terminal
+    }
+    /// Get the number of normal states whose dice count differs.
+    #[must_use]
+    pub fn normal(&self) -> usize {
+        self.normal
+    }
+    /// Get the number of terminal states whose dice count differs.
+    #[must_use]
+    pub fn terminal(&self) -> usize {
+        self.terminal
+    }
+    /// Get the largest absolute difference between payoffs.
+    #[must_use]
+    pub fn max_payoff_difference(&self) -> f64 {
+        self.max_payoff_difference
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::dp::PmfBackend;
+    use crate::{DoubleDouble, DpSolver, Solver};
+
+    #[test]
+    fn test_precisions_agree() {
+        let f64_policy = DpSolver::new(40, 6).policy();
+        let dd_policy = DpSolver::new(40, 6)
+            .with_pmf_backend(PmfBackend::Compensated)
+            .with_precision::<DoubleDouble>()
+            .policy();
+
+        let same = PrecisionDiff::new(&f64_policy, &f64_policy);
+        assert_eq!(same.decisions(), 0);
+        assert_eq!(same.max_payoff_difference(), 0.0);
+
+        let diff = PrecisionDiff::new(&f64_policy, &dd_policy);
+        assert_eq!(diff.states(), 2 * 41 * 41);
+        assert!(diff.max_payoff_difference() < 1e-12, "{diff:?}");
+    }
+
+    #[test]
+    fn test_f32_policy_is_close() {
+        let f64_policy = DpSolver::new(40, 6).policy();
+        let f32_policy = DpSolver::new(40, 6).with_precision::<f32>().policy();
+
+        let diff = PrecisionDiff::new(&f32_policy, &f64_policy);
+        // Rounding to f32 moves payoffs but changes no decision on this board.
+        assert!(
+            (diff.max_payoff_difference() - 3.7954e-7).abs() < 1e-11,
+            "{diff:?}"
+        );
+        assert_eq!(diff.decisions(), 0, "{diff:?}");
+        assert_eq!(f32_policy.memory_usage() * 2, f64_policy.memory_usage());
+    }
+}
@@ -0,0 +1,133 @@
+//! Measure what rolling one die at a time is worth over committing up front.
+
+use crate::{DpSolver, Policy, PushPolicy, PushSolver, Ruleset, Solver, State};
+
+/// Compares one-die-at-a-time turns (see `PushSolver`) with committing to a
+/// number of dice up front, under the same ruleset.
+///
+/// Both players see every die in the push variant, so the first player's
+/// payoff can move either way. The cleanest measure of the information is the
+/// final round, where only the mover decides: there, seeing each die is never
+/// worse, and the gain is the difference in payoff for the same state.
+#[derive(Debug, Copy, Clone)]
+pub struct PushValue {
+    /// The ruleset being analyzed.
+    ruleset: Ruleset,
+    /// The first player's payoff when committing up front.
+    commit: f64,
+    /// The first player's payoff when rolling one die at a time.
+    push: f64,
+    /// The mean final-round gain over the states where the mover is not
+    /// ahead.
+    mean_gain: f64,
+    /// The final-round state with the largest gain, and the gain.
+    largest_gain: (State, f64),
+    /// The fraction of normal mid-turn states that roll again.
+    rolls_on: f64,
+}
+
+impl PushValue {
+    /// Compare a solved commit-up-front policy with a solved
+    /// one-die-at-a-time policy for `ruleset`.
+    #[must_use]
+    pub fn new(ruleset: Ruleset, commit: &Policy, push: &PushPolicy) -> Self {
+        let start = ruleset.initial_state();
+        let gains: Vec<(State, f64)> = commit
+            .iter()
+            .filter(|(state, _)| state.last() && state.active() <= state.queued())
+            .map(|(state, action)| {
+                let gain = push.turn_start().get(&state).payoff() - action.payoff();
+                (state, gain)
+            })
+            .collect();
+        let largest_gain = gains
+            .iter()
+            .copied()
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .unwrap();
+
+        // A turn is only midway once it has rolled, so onto at least 1.
+        let (rolls, states) = push
+            .mid_turn()
+            .iter()
+            .filter(|(state, _)| !state.last() && state.active() > 0)
+            .fold((0, 0), |(rolls, states), (_, action)| {
+                (rolls + action.n() as usize, states + 1)
+            });
+
+        Self {
+            ruleset,
+            commit: commit.get(&start).payoff(),
+            push: push.turn_start().get(&start).payoff(),
+            mean_gain: gains.iter().map(|(_, gain)| gain).sum::<f64>() / gains.len() as f64,
+            largest_gain,
+            rolls_on: rolls as f64 / states as f64,
+        }
+    }
+    /// Get the ruleset being analyzed.
+    #[must_use]
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+    /// Get the first player's payoff when committing up front.
+    #[must_use]
+    pub fn commit(&self) -> f64 {
+        self.commit
+    }
+    /// Get the first player's payoff when rolling one die at a time.
+    #[must_use]
+    pub fn push(&self) -> f64 {
+        self.push
+    }
+    /// Get the mean gain in payoff from rolling one die at a time in the
+    /// final round, over the states where the mover is not ahead (the others
+    /// stand and win either way).
+    #[must_use]
+    pub fn mean_gain(&self) -> f64 {
+        self.mean_gain
+    }
+    /// Get the final-round state where rolling one die at a time gains the
+    /// most, and the gain.
+    #[must_use]
+    pub fn largest_gain(&self) -> (State, f64) {
+        self.largest_gain
+    }
+    /// Get the fraction of normal (not final-round) mid-turn states in which
+    /// the mover rolls again rather than passing the turn.
+    #[must_use]
+    pub fn rolls_on(&self) -> f64 {
+        self.rolls_on
+    }
+}
+
+/// Solve a ruleset under both kinds of turn and compare them.
+///
+/// # Panics
+///
+/// Panics if `PushSolver` does not support the ruleset.
+#[must_use]
+pub fn push_value(ruleset: Ruleset) -> PushValue {
+    let commit = DpSolver::from_ruleset(ruleset).policy();
+    let push = PushSolver::from_ruleset(ruleset).policy();
+    PushValue::new(ruleset, &commit, &push)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_final_round_gains() {
+        let report = push_value(Ruleset::new(30, 6));
+        assert!((report.mean_gain() - 0.330280).abs() < 1e-6);
+
+        // Trailing far behind, a fixed number of dice often overshoots.
+        let (state, gain) = report.largest_gain();
+        assert_eq!(state, State::new(1, 25, true));
+        assert!((gain - 1.081741).abs() < 1e-6);
+
+        // Every die is added to the score, so nothing is gained by rolling on
+        // rather than seeing the opponent's next die first.
+        assert_eq!(report.rolls_on(), 0.0);
+    }
+}
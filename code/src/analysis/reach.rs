@@ -0,0 +1,127 @@
+//! Probability of visiting each state during a game.
+
+use super::markov::Chain;
+use crate::{Policy, Ruleset, State};
+
+/// The probability that each state is visited during a game.
+///
+/// A game never visits the same state twice (every roll strictly increases the
+/// combined score, and a game ends by the second of two consecutive stands), so the
+/// visit probability of a state is the sum of its occupancy over all turns.
+/// Unreachable states have probability exactly zero.
+///
+/// With a soft `BustRule` a state can be revisited, so this is the expected
+/// number of visits instead (which can exceed 1).
+#[derive(Debug, Clone)]
+pub struct Reachability {
+    /// Visit probabilities, laid out like the policy table.
+    visits: Box<[f64]>,
+    /// A table with the same layout, used for indexing.
+    layout: Policy,
+}
+
+impl Reachability {
+    /// Compute visit probabilities from `start` when the first player (to
+    /// move in `start`) follows `policies.0` and the second follows
+    /// `policies.1`.
+    #[must_use]
+    pub fn new(ruleset: Ruleset, start: State, policies: (&Policy, &Policy)) -> Self {
+        let layout = Policy::new(ruleset.max());
+        let mut visits = vec![0.0; layout.len()];
+        Chain::new(ruleset, policies).run(start, |_, dist, _| {
+            for (visit, p) in visits.iter_mut().zip(dist) {
+                *visit += p;
+            }
+        });
+        Self {
+            visits: visits.into_boxed_slice(),
+            layout,
+        }
+    }
+    /// Get the probability that `state` is visited.
+    #[must_use]
+    pub fn get(&self, state: &State) -> f64 {
+        self.visits[self.layout.index(state)]
+    }
+    /// Iterate over every state and its visit probability.
+    pub fn iter(&self) -> impl Iterator<Item = (State, f64)> + '_ {
+        self.visits
+            .iter()
+            .enumerate()
+            .map(|(placement, &visit)| (self.layout.state(placement), visit))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{DpSolver, Solver};
+
+    #[test]
+    fn test_reachability_is_a_probability() {
+        let policy = DpSolver::new(30, 6).policy();
+        let ruleset = Ruleset::new(30, 6);
+        let reach = Reachability::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+
+        assert!((reach.get(&ruleset.initial_state()) - 1.0).abs() < 1e-12);
+        assert!(
+            reach
+                .iter()
+                .all(|(_, p)| (-1e-12..=1.0 + 1e-12).contains(&p))
+        );
+        // Nobody can be on 1 point after the first player rolls many dice.
+        assert_eq!(reach.get(&State::new(0, 1, false)), 0.0);
+    }
+
+    /// Add `p` to the visits of `state` and of every state after it, playing
+    /// out each face of each die (with `EndRule::LastTurn` and
+    /// `BustRule::Lose`).
+    fn enumerate(
+        ruleset: Ruleset,
+        policy: &Policy,
+        state: State,
+        p: f64,
+        visits: &mut HashMap<State, f64>,
+    ) {
+        *visits.entry(state).or_default() += p;
+        let n = policy.get(&state).n();
+        if state.last() {
+            return; // The final roll ends the game.
+        }
+        if n == 0 {
+            let stood = State::new(state.queued(), state.active(), true);
+            return enumerate(ruleset, policy, stood, p, visits);
+        }
+        let sides = ruleset.sides();
+        let p = p / f64::from(sides.pow(n));
+        for faces in 0..sides.pow(n) {
+            let total: u32 = (0..n).map(|die| faces / sides.pow(die) % sides + 1).sum();
+            if state.active() + total <= ruleset.max() {
+                let rolled = State::new(state.queued(), state.active() + total, false);
+                enumerate(ruleset, policy, rolled, p, visits);
+            }
+        }
+    }
+
+    #[test]
+    fn test_final_round_reached_at_most_once() {
+        let ruleset = Ruleset::new(10, 2);
+        let policy = DpSolver::from_ruleset(ruleset).policy();
+        let reach = Reachability::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+
+        // Every game either busts in the normal round or enters the final
+        // round exactly once.
+        let last: f64 = reach.iter().filter(|(s, _)| s.last()).map(|(_, p)| p).sum();
+        assert!(last <= 1.0 + 1e-10);
+
+        // Every visit matches playing out every roll die by die.
+        let mut visits = HashMap::new();
+        enumerate(ruleset, &policy, ruleset.initial_state(), 1.0, &mut visits);
+        for (state, p) in reach.iter() {
+            let expected = visits.get(&state).copied().unwrap_or(0.0);
+            assert!((p - expected).abs() < 1e-12, "{state:?}: {p} vs {expected}");
+        }
+    }
+}
@@ -0,0 +1,196 @@
+//! Solve a grid of rulesets and summarize each optimal policy.
+
+use std::ops::RangeInclusive;
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use rayon::prelude::*;
+
+use crate::{MultiMaxSolver, Policy, Ruleset, State};
+
+/// Summary statistics of the optimal policy for a single ruleset.
+#[derive(Debug, Copy, Clone)]
+pub struct SweepRow {
+    /// The ruleset that was solved.
+    ruleset: Ruleset,
+    /// The payoff of the first player from the initial state.
+    payoff: f64,
+    /// The mean optimal dice count over the opening states.
+    opening_n: f64,
+    /// The smallest stand threshold over all opponent scores.
+    threshold_min: u32,
+    /// The mean stand threshold over all opponent scores.
+    threshold_mean: f64,
+    /// The largest stand threshold over all opponent scores.
+    threshold_max: u32,
+    /// Wall-clock time spent solving the ruleset.
+    solve_time: Duration,
+}
+
+impl SweepRow {
+    /// Summarize a solved policy.
+    ///
+    /// The opening states are the first turns of either player, `(0, queued,
+    /// normal)` for every opponent score. The stand threshold for an opponent
+    /// score is the lowest active score at which the optimal action is to
+    /// stand.
+    #[must_use]
+    pub fn new(ruleset: Ruleset, policy: &Policy, solve_time: Duration) -> Self {
+        let max = ruleset.max();
+        let payoff = policy.get(&State::new(0, 0, false)).payoff();
+        let opening_n = (0..=max)
+            .map(|queued| f64::from(policy.get(&State::new(0, queued, false)).n()))
+            .sum::<f64>()
+            / f64::from(max + 1);
+
+        // Standing at the maximum score is always optimal, so every threshold
+        // exists.
+        let thresholds: Vec<u32> = (0..=max)
+            .map(|queued| {
+                (0..=max)
+                    .find(|&active| policy.get(&State::new(active, queued, false)).n() == 0)
+                    .unwrap_or(max)
+            })
+            .collect();
+        let threshold_mean =
+            thresholds.iter().map(|&t| f64::from(t)).sum::<f64>() / f64::from(max + 1);
+
+        Self {
+            ruleset,
+            payoff,
+            opening_n,
+            threshold_min: *thresholds.iter().min().unwrap_or(&0),
+            threshold_mean,
+            threshold_max: *thresholds.iter().max().unwrap_or(&0),
+            solve_time,
+        }
+    }
+    /// Get the ruleset that was solved.
+    #[must_use]
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+    /// Get the payoff of the first player from the initial state.
+    #[must_use]
+    pub fn payoff(&self) -> f64 {
+        self.payoff
+    }
+    /// Get the mean optimal dice count over the opening states.
+    #[must_use]
+    pub fn opening_n(&self) -> f64 {
+        self.opening_n
+    }
+    /// Get the (min, mean, max) stand threshold over all opponent scores.
+    #[must_use]
+    pub fn thresholds(&self) -> (u32, f64, u32) {
+        (self.threshold_min, self.threshold_mean, self.threshold_max)
+    }
+    /// Get the wall-clock time spent solving the ruleset.
+    #[must_use]
+    pub fn solve_time(&self) -> Duration {
+        self.solve_time
+    }
+}
+
+/// Solve every combination of maximum score and die sides in parallel.
+///
+/// A policy for a smaller maximum score is a translated corner of the policy
+/// for a larger one, so each die is only solved once, for the largest maximum
+/// score, with a `MultiMaxSolver`. The solve time of a row is the time of this
+/// shared solve plus the time to extract and summarize its policy.
+///
+/// Rows are returned ordered by maximum score, then by sides.
+#[must_use]
+pub fn sweep(maxes: RangeInclusive<u32>, sides: RangeInclusive<u32>) -> Vec<SweepRow> {
+    let mut rows: Vec<SweepRow> = sides
+        .into_par_iter()
+        .flat_map(|sides| {
+            let start = Instant::now();
+            let solved = OnceLock::new();
+            MultiMaxSolver::new(maxes.clone(), sides).solve_map(|ruleset, policy| {
+                let shared = *solved.get_or_init(|| start.elapsed());
+                let extract = Instant::now();
+                let mut row = SweepRow::new(ruleset, &policy, Duration::ZERO);
+                row.solve_time = shared + extract.elapsed();
+                row
+            })
+        })
+        .collect();
+    rows.sort_by_key(|row| (row.ruleset.max(), row.ruleset.sides()));
+    rows
+}
+
+impl SweepRow {
+    /// Export sweep results to a CSV file.
+    ///
+    /// Creates a CSV with columns: max, sides, payoff, opening_n,
+    /// threshold_min, threshold_mean, threshold_max, solve_ms
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written to.
+    pub fn csv(rows: &[SweepRow], path: &str) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_path(path)?;
+
+        // Write headers
+        writer.serialize((
+            "max",
+            "sides",
+            "payoff",
+            "opening_n",
+            "threshold_min",
+            "threshold_mean",
+            "threshold_max",
+            "solve_ms",
+        ))?;
+        for row in rows {
+            writer.serialize((
+                row.ruleset.max(),
+                row.ruleset.sides(),
+                row.payoff,
+                row.opening_n,
+                row.threshold_min,
+                row.threshold_mean,
+                row.threshold_max,
+                row.solve_time.as_secs_f64() * 1000.0,
+            ))?;
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DpSolver, Solver};
+
+    #[test]
+    fn test_sweep_covers_grid() {
+        let rows = sweep(10..=12, 2..=3);
+        let rulesets: Vec<_> = rows
+            .iter()
+            .map(|row| (row.ruleset().max(), row.ruleset().sides()))
+            .collect();
+        assert_eq!(
+            rulesets,
+            [(10, 2), (10, 3), (11, 2), (11, 3), (12, 2), (12, 3)]
+        );
+    }
+
+    #[test]
+    fn test_sweep_row_matches_policy() {
+        let policy = DpSolver::new(20, 4).policy();
+        let row = SweepRow::new(Ruleset::new(20, 4), &policy, Duration::ZERO);
+
+        let payoff = policy.get(&State::new(0, 0, false)).payoff();
+        assert!((row.payoff() - payoff).abs() < 1e-12);
+
+        // The thresholds over the 21 opponent scores sum to 372.
+        let (min, mean, max) = row.thresholds();
+        assert_eq!((min, max), (17, 20));
+        assert!((mean - 372.0 / 21.0).abs() < 1e-12);
+        assert!((row.opening_n() - 125.0 / 21.0).abs() < 1e-12);
+        assert!((0..=20).any(|queued| policy.get(&State::new(min, queued, false)).n() == 0));
+    }
+}
@@ -0,0 +1,361 @@
+//! Compact, action-only policies for embedding.
+
+use std::io;
+
+use crate::{Policy, Scalar, State};
+
+/// How a `CompactPolicy` stores its dice counts.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Encoding {
+    /// One code per state, in the same layout as `Policy`.
+    #[default]
+    Raw,
+    /// Runs of equal dice counts along each row (a fixed queued score and
+    /// round), stored as (n, length) pairs.
+    ///
+    /// Optimal dice counts change slowly with the active score, so a row only
+    /// has a handful of runs.
+    RunLength,
+}
+
+/// The integer width of the stored codes.
+#[derive(Debug, Clone)]
+enum Codes {
+    /// Every code fits in a `u8`.
+    U8(Box<[u8]>),
+    /// Some code needs a `u16`.
+    U16(Box<[u16]>),
+}
+
+impl Codes {
+    /// Get the code at `index`.
+    #[inline]
+    fn at(&self, index: usize) -> u32 {
+        match self {
+            Self::U8(codes) => u32::from(codes[index]),
+            Self::U16(codes) => u32::from(codes[index]),
+        }
+    }
+    /// Returns the number of codes.
+    fn len(&self) -> usize {
+        match self {
+            Self::U8(codes) => codes.len(),
+            Self::U16(codes) => codes.len(),
+        }
+    }
+    /// Returns the size of one code in bytes.
+    fn width(&self) -> usize {
+        match self {
+            Self::U8(_) => 1,
+            Self::U16(_) => 2,
+        }
+    }
+}
+
+/// An action-only policy, for shipping inside apps where memory is scarce.
+///
+/// A `Policy` stores a full `Action` (dice count and payoff) per state, which
+/// is 16 bytes for `f64` payoffs. Playing only needs the dice count, which
+/// fits in a `u8` (or a `u16` for very large rulesets), and run-length coding
+/// shrinks it further: the standard (100, 6) policy takes a few kilobytes.
+///
+/// # Memory Layout
+///
+/// With `Encoding::Raw`, codes are laid out exactly like `Policy`. With
+/// `Encoding::RunLength`, the rows (`queued + (max+1) × last`) are stored one
+/// after another as alternating (n, length) codes, with the start of each row
+/// kept in a separate index. Runs longer than the code width allows are
+/// split.
+#[derive(Debug, Clone)]
+pub struct CompactPolicy {
+    /// The maximum score.
+    max: u32,
+    /// How the codes are laid out.
+    encoding: Encoding,
+    /// The dice counts (raw) or (n, length) pairs (run-length).
+    codes: Codes,
+    /// The index of the first code of each row (run-length only).
+    rows: Box<[u32]>,
+}
+
+/// Identifies the byte format of a `CompactPolicy`.
+const MAGIC: &[u8; 4] = b"GRDP";
+/// The version of the byte format.
+const VERSION: u8 = 1;
+
+impl CompactPolicy {
+    /// Compress the dice counts of a policy, dropping its payoffs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if some dice count does not fit in a `u16`.
+    #[must_use]
+    pub fn new<T: Scalar>(policy: &Policy<T>, encoding: Encoding) -> Self {
+        let max = policy.max();
+        let stride = max as usize + 1;
+        let values: Vec<u32> = policy.iter().map(|(_, action)| action.n()).collect();
+
+        // Runs are split so that their lengths fit in the code width too.
+        let largest = values.iter().copied().max().unwrap_or(0);
+        let limit = if largest <= u32::from(u8::MAX) {
+            u32::from(u8::MAX)
+        } else {
+            assert!(
+                largest <= u32::from(u16::MAX),
+                "dice counts must fit in a u16"
+            );
+            u32::from(u16::MAX)
+        };
+
+        let codes: Vec<u32> = match encoding {
+            Encoding::Raw => values,
+            Encoding::RunLength => values
+                .chunks(stride)
+                .flat_map(|row| {
+                    let mut runs = Vec::new();
+                    for &n in row {
+                        match runs.last_mut() {
+                            Some((value, length)) if *value == n => *length += 1,
+                            _ => runs.push((n, 1)),
+                        }
+                    }
+                    let runs: Vec<u32> = runs
+                        .into_iter()
+                        .flat_map(|(n, length)| [n, length])
+                        .collect();
+                    split_runs(&runs, limit)
+                })
+                .collect(),
+        };
+
+        let codes = if limit == u32::from(u8::MAX) {
+            Codes::U8(codes.iter().map(|&c| c as u8).collect())
+        } else {
+            Codes::U16(codes.iter().map(|&c| c as u16).collect())
+        };
+        Self::from_codes(max, encoding, codes).expect("encoded rows are well-formed")
+    }
+    /// Build the row index of the codes, checking that they describe every
+    /// state exactly once.
+    fn from_codes(max: u32, encoding: Encoding, codes: Codes) -> io::Result<Self> {
+        let stride = u64::from(max) + 1;
+        let states = stride
+            .checked_mul(stride)
+            .and_then(|states| states.checked_mul(2))
+            .ok_or_else(|| invalid("maximum score too large"))?;
+        let mut rows = Vec::new();
+        match encoding {
+            Encoding::Raw => {
+                if codes.len() as u64 != states {
+                    return Err(invalid("wrong number of codes"));
+                }
+            }
+            Encoding::RunLength => {
+                if !codes.len().is_multiple_of(2) {
+                    return Err(invalid("unpaired run"));
+                }
+                let mut covered = 0;
+                for pair in 0..codes.len() / 2 {
+                    if covered % stride == 0 {
+                        rows.push(2 * pair as u32);
+                    }
+                    let length = u64::from(codes.at(2 * pair + 1));
+                    if length == 0 || covered % stride + length > stride {
+                        return Err(invalid("run crosses a row"));
+                    }
+                    covered += length;
+                }
+                if covered != states {
+                    return Err(invalid("runs do not cover every state"));
+                }
+            }
+        }
+        Ok(Self {
+            max,
+            encoding,
+            codes,
+            rows: rows.into_boxed_slice(),
+        })
+    }
+    /// Retrieve the optimal number of dice to roll in a given game state.
+    ///
+    /// Only the dice count is stored, not the payoff. With run-length coding
+    /// this walks the runs of the state's row.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, state: &State) -> u32 {
+        let stride = u64::from(self.max) + 1;
+        let row = u64::from(state.queued()) + stride * u64::from(state.last());
+        match self.encoding {
+            Encoding::Raw => self
+                .codes
+                .at((u64::from(state.active()) + stride * row) as usize),
+            Encoding::RunLength => {
+                let mut index = self.rows[row as usize] as usize;
+                let mut remaining = state.active();
+                loop {
+                    let length = self.codes.at(index + 1);
+                    if remaining < length {
+                        return self.codes.at(index);
+                    }
+                    remaining -= length;
+                    index += 2;
+                }
+            }
+        }
+    }
+    /// Iterate over every state and its optimal number of dice.
+    pub fn iter(&self) -> impl Iterator<Item = (State, u32)> + '_ {
+        let stride = u64::from(self.max) + 1;
+        (0..stride * stride * 2).map(move |placement| {
+            // Both scores are at most `max`, so they fit in a `u32`.
+            let last = placement >= stride * stride;
+            let placement = placement % (stride * stride);
+            let state = State::new(
+                (placement % stride) as u32,
+                (placement / stride) as u32,
+                last,
+            );
+            (state, self.get(&state))
+        })
+    }
+    /// Returns the maximum score.
+    #[must_use]
+    pub fn max(&self) -> u32 {
+        self.max
+    }
+    /// Returns the encoding of the codes.
+    #[must_use]
+    pub fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+    /// Returns the memory used by the codes and row index in bytes.
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        self.codes.len() * self.codes.width() + std::mem::size_of_val(&*self.rows)
+    }
+    /// Serialize the policy.
+    ///
+    /// The format is the magic bytes `GRDP`, a version byte, the encoding
+    /// (0 = raw, 1 = run-length), the code width in bytes, the maximum score
+    /// and number of codes (both `u32`), then the codes. All integers are
+    /// little-endian. The row index is rebuilt on load.
+    #[must_use]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(15 + self.codes.len() * self.codes.width());
+        bytes.extend_from_slice(MAGIC);
+        bytes.push(VERSION);
+        bytes.push(match self.encoding {
+            Encoding::Raw => 0,
+            Encoding::RunLength => 1,
+        });
+        bytes.push(self.codes.width() as u8);
+        bytes.extend_from_slice(&self.max.to_le_bytes());
+        bytes.extend_from_slice(&(self.codes.len() as u32).to_le_bytes());
+        match &self.codes {
+            Codes::U8(codes) => bytes.extend_from_slice(codes),
+            Codes::U16(codes) => bytes.extend(codes.iter().flat_map(|c| c.to_le_bytes())),
+        }
+        bytes
+    }
+    /// Deserialize a policy written by `to_bytes`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an `InvalidData` error if the bytes are not a well-formed
+    /// policy.
+    pub fn from_bytes(bytes: &[u8]) -> io::Result<Self> {
+        let header = bytes.get(..15).ok_or_else(|| invalid("truncated header"))?;
+        if &header[..4] != MAGIC || header[4] != VERSION {
+            return Err(invalid("not a compact policy"));
+        }
+        let encoding = match header[5] {
+            0 => Encoding::Raw,
+            1 => Encoding::RunLength,
+            _ => return Err(invalid("unknown encoding")),
+        };
+        let max = u32::from_le_bytes(header[7..11].try_into().unwrap());
+        let len = u32::from_le_bytes(header[11..15].try_into().unwrap()) as usize;
+        let body = &bytes[15..];
+        let codes = match header[6] {
+            1 if body.len() == len => Codes::U8(body.into()),
+            2 if body.len() == 2 * len => Codes::U16(
+                body.chunks_exact(2)
+                    .map(|c| u16::from_le_bytes([c[0], c[1]]))
+                    .collect(),
+            ),
+            1 | 2 => return Err(invalid("wrong number of codes")),
+            _ => return Err(invalid("unknown code width")),
+        };
+        Self::from_codes(max, encoding, codes)
+    }
+}
+
+/// Split (n, length) runs so that no length exceeds `limit`.
+fn split_runs(codes: &[u32], limit: u32) -> Vec<u32> {
+    let mut split = Vec::with_capacity(codes.len());
+    for run in codes.chunks_exact(2) {
+        let (n, mut length) = (run[0], run[1]);
+        while length > limit {
+            split.extend([n, limit]);
+            length -= limit;
+        }
+        split.extend([n, length]);
+    }
+    split
+}
+
+/// An `InvalidData` error.
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DpSolver, Solver};
+
+    #[test]
+    fn test_compact_matches_policy() {
+        let policy = DpSolver::new(60, 6).policy();
+        let raw = CompactPolicy::new(&policy, Encoding::Raw);
+        let rle = CompactPolicy::new(&policy, Encoding::RunLength);
+
+        for (state, action) in policy.iter() {
+            assert_eq!(raw.get(&state), action.n(), "{state:?}");
+            assert_eq!(rle.get(&state), action.n(), "{state:?}");
+        }
+        assert!(rle.memory_usage() < raw.memory_usage());
+        assert!(raw.memory_usage() * 16 == policy.memory_usage());
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let policy = DpSolver::new(30, 4).policy();
+        for encoding in [Encoding::Raw, Encoding::RunLength] {
+            let compact = CompactPolicy::new(&policy, encoding);
+            let bytes = compact.to_bytes();
+            let loaded = CompactPolicy::from_bytes(&bytes).unwrap();
+            assert_eq!(loaded.encoding(), encoding);
+            assert!(compact.iter().eq(loaded.iter()));
+
+            assert!(CompactPolicy::from_bytes(&bytes[..bytes.len() - 1]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_overflowing_header_is_rejected() {
+        // A maximum of `u32::MAX` overflows the number of states.
+        let mut bytes = MAGIC.to_vec();
+        bytes.extend([VERSION, 0, 1]);
+        bytes.extend(u32::MAX.to_le_bytes());
+        bytes.extend(0u32.to_le_bytes());
+        let error = CompactPolicy::from_bytes(&bytes).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+
+    #[test]
+    fn test_long_runs_are_split() {
+        assert_eq!(split_runs(&[3, 10, 4, 2], 4), [3, 4, 3, 4, 3, 2, 4, 2]);
+    }
+}
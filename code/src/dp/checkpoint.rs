@@ -0,0 +1,232 @@
+//! Checkpoints of a partially solved normal-state sweep.
+//!
+//! `DpSolver::solve_normal_states` finishes one order (active + queued) at a
+//! time, from `2 × max` down to 0, so between two orders the solve is fully
+//! described by:
+//!
+//! + the terminal actions (which every normal state stands into),
+//! + the actions of the orders solved so far,
+//! + the next order to solve, and
+//! + the batched payoffs `Wⁿ(x)` still needed by the remaining orders.
+//!
+//! # File Format
+//!
+//! All integers are little-endian `u32`s and payoffs are written with
+//! `Scalar::write_le`:
+//!
+//! + the magic bytes `GRDK` and a version byte,
+//! + the scalar name (a length byte, then the bytes),
+//! + the end rule (its position in `EndRule::ALL`, as a byte),
+//! + whether landing exactly on the maximum wins (a byte, 0 or 1),
+//! + the tie rule (its position in `TieRule::ALL`, as a byte),
+//! + the PMF backend (its position in `PmfBackend::ALL`, as a byte),
+//! + the maximum score, die sides, dice limit (0 if unlimited), next order to
+//!   solve, and window width,
+//! + every terminal action, with `active` varying fastest (with
+//!   `EndRule::BothStand`, only those of the solved orders are meaningful),
+//! + every solved normal action, order by order from `2 × max`, and
+//! + the window of every opponent score.
+//!
+//! An action is its dice count followed by its payoff.
+
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter, Read, Write};
+use std::path::Path;
+
+use super::dp::Windows;
+use super::pmf::PmfBackend;
+use crate::{Action, BustRule, EndRule, Policy, Ruleset, Scalar, State, TieRule};
+
+/// Identifies a checkpoint file.
+const MAGIC: &[u8; 4] = b"GRDK";
+/// The version of the checkpoint format.
+const VERSION: u8 = 6;
+
+/// A partially solved normal-state sweep, as read from a checkpoint.
+pub(super) struct Checkpoint<T: Scalar> {
+    /// The policy, with every terminal state and the solved orders filled in.
+    pub(super) policy: Policy<T>,
+    /// The next order to solve.
+    pub(super) order: u32,
+    /// The batched payoffs after the solved orders.
+    pub(super) windows: Windows<T>,
+}
+
+/// Iterate over the normal states of an order.
+fn order_states(max: u32, order: u32) -> impl Iterator<Item = State> {
+    let lowest = order.saturating_sub(max);
+    let places = order.min(2 * max - order);
+    (lowest..=lowest + places).map(move |next| State::new(order - next, next, false))
+}
+
+/// Write a checkpoint before solving `order`, whose payoffs were computed
+/// with the PMFs of `backend`.
+///
+/// The file is written next to `path` and then renamed over it, so an
+/// interrupted write never corrupts the previous checkpoint.
+pub(super) fn write<T: Scalar>(
+    path: &Path,
+    ruleset: Ruleset,
+    backend: PmfBackend,
+    order: u32,
+    policy: &Policy<T>,
+    windows: &Windows<T>,
+) -> io::Result<()> {
+    let (max, sides) = (ruleset.max(), ruleset.sides());
+    let temp = path.with_extension("tmp");
+    let mut writer = BufWriter::new(File::create(&temp)?);
+
+    writer.write_all(MAGIC)?;
+    writer.write_all(&[VERSION, T::NAME.len() as u8])?;
+    writer.write_all(T::NAME.as_bytes())?;
+    writer.write_all(&[
+        end_rule_code(ruleset.end_rule()),
+        u8::from(ruleset.exact_win()),
+        tie_rule_code(ruleset.tie_rule()),
+        backend_code(backend),
+    ])?;
+    let max_dice = ruleset.max_dice().unwrap_or(0);
+    for value in [max, sides, max_dice, order, windows.width() as u32] {
+        writer.write_all(&value.to_le_bytes())?;
+    }
+
+    let write_action = |writer: &mut BufWriter<File>, action: Action<T>| {
+        writer.write_all(&action.n().to_le_bytes())?;
+        action.payoff().write_le(writer)
+    };
+    for queued in 0..=max {
+        for active in 0..=max {
+            write_action(&mut writer, policy.get(&State::new(active, queued, true)))?;
+        }
+    }
+    for solved in (order + 1..=2 * max).rev() {
+        for state in order_states(max, solved) {
+            write_action(&mut writer, policy.get(&state))?;
+        }
+    }
+    for value in windows.values() {
+        value.write_le(&mut writer)?;
+    }
+
+    writer.into_inner().map_err(io::Error::from)?.sync_all()?;
+    fs::rename(temp, path)
+}
+
+/// Read a checkpoint written by `write` for `ruleset` and `backend`.
+///
+/// Soft busts are solved by value iteration, which is never checkpointed, so
+/// no checkpoint is for a ruleset with a soft `BustRule`.
+pub(super) fn read<T: Scalar>(
+    path: &Path,
+    ruleset: Ruleset,
+    backend: PmfBackend,
+) -> io::Result<Checkpoint<T>> {
+    if ruleset.bust_rule() != BustRule::Lose {
+        return Err(invalid("soft busts cannot be resumed from a checkpoint"));
+    }
+    let (max, sides) = (ruleset.max(), ruleset.sides());
+    let mut reader = BufReader::new(File::open(path)?);
+    let read_u32 = |reader: &mut BufReader<File>| {
+        let mut bytes = [0; 4];
+        reader.read_exact(&mut bytes)?;
+        Ok::<_, io::Error>(u32::from_le_bytes(bytes))
+    };
+
+    let mut header = [0; 6];
+    reader.read_exact(&mut header)?;
+    if &header[..4] != MAGIC || header[4] != VERSION {
+        return Err(invalid("not a checkpoint"));
+    }
+    let mut name = vec![0; header[5] as usize];
+    reader.read_exact(&mut name)?;
+    if name != T::NAME.as_bytes() {
+        return Err(invalid("checkpoint was written with a different precision"));
+    }
+    let mut rules = [0; 4];
+    reader.read_exact(&mut rules)?;
+    if rules[0] != end_rule_code(ruleset.end_rule()) {
+        return Err(invalid("checkpoint is for a different end rule"));
+    }
+    if rules[1] != u8::from(ruleset.exact_win()) {
+        return Err(invalid("checkpoint is for a different exact-win rule"));
+    }
+    if rules[2] != tie_rule_code(ruleset.tie_rule()) {
+        return Err(invalid("checkpoint is for a different tie rule"));
+    }
+    if rules[3] != backend_code(backend) {
+        return Err(invalid(
+            "checkpoint was written with a different PMF backend",
+        ));
+    }
+    let dimensions = (read_u32(&mut reader)?, read_u32(&mut reader)?);
+    let max_dice = read_u32(&mut reader)?;
+    if dimensions != (max, sides) || max_dice != ruleset.max_dice().unwrap_or(0) {
+        return Err(invalid("checkpoint is for a different ruleset"));
+    }
+    let order = read_u32(&mut reader)?;
+    if order > 2 * max {
+        return Err(invalid("next order is out of range"));
+    }
+    let mut windows = Windows::new(max, sides);
+    if read_u32(&mut reader)? as usize != windows.width() {
+        return Err(invalid("checkpoint has a different window width"));
+    }
+
+    let mut policy = Policy::new(max);
+    let mut read_action = |reader: &mut BufReader<File>, state: State| {
+        let n = read_u32(reader)?;
+        policy.set(&state, Action::new(n, T::read_le(reader)?));
+        Ok::<_, io::Error>(())
+    };
+    for queued in 0..=max {
+        for active in 0..=max {
+            read_action(&mut reader, State::new(active, queued, true))?;
+        }
+    }
+    for solved in (order + 1..=2 * max).rev() {
+        for state in order_states(max, solved) {
+            read_action(&mut reader, state)?;
+        }
+    }
+    for value in windows.values_mut() {
+        *value = T::read_le(&mut reader)?;
+    }
+    if reader.read(&mut [0])? != 0 {
+        return Err(invalid("trailing bytes after checkpoint"));
+    }
+
+    Ok(Checkpoint {
+        policy,
+        order,
+        windows,
+    })
+}
+
+/// The byte identifying an end rule.
+fn end_rule_code(end_rule: EndRule) -> u8 {
+    EndRule::ALL
+        .iter()
+        .position(|&rule| rule == end_rule)
+        .unwrap() as u8
+}
+
+/// The byte identifying a tie rule.
+fn tie_rule_code(tie_rule: TieRule) -> u8 {
+    TieRule::ALL
+        .iter()
+        .position(|&rule| rule == tie_rule)
+        .unwrap() as u8
+}
+
+/// The byte identifying a PMF backend.
+fn backend_code(backend: PmfBackend) -> u8 {
+    PmfBackend::ALL
+        .iter()
+        .position(|&other| other == backend)
+        .unwrap() as u8
+}
+
+/// An `InvalidData` error.
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message)
+}
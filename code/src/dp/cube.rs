@@ -0,0 +1,353 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+
+use super::dp::PMFLookup;
+use crate::{
+    Action, BustRule, CubeAction, CubeOwner, CubePolicy, EndRule, Ruleset, Scalar, State,
+    UnsupportedRuleset,
+};
+
+/// Solves Greed with a doubling cube, as in backgammon: before rolling, a
+/// player may offer to double the stakes, and the opponent either takes (and
+/// owns the cube, so only they may double next) or concedes at the current
+/// stakes.
+///
+/// Every payoff scales with the stakes, so a state's payoff is kept in units
+/// of the current stake and the value of the cube drops out: only who owns
+/// it (see `CubeOwner`) is part of the state. With `R(s, c)` the payoff of
+/// the best roll in `s` with owner `c`, and the rolls continuing into the
+/// opponent's states with the owner seen from their side,
+///
+/// + a taken double is worth `2 × R(s, Opponent)` to the doubler, and a
+///   dropped one 1, so the opponent takes unless that exceeds 1;
+/// + doubling is worth `min(1, 2 × R(s, Opponent))`, and the mover doubles
+///   (if the cube allows it) when that beats `R(s, c)`.
+///
+/// Every roll still increases the combined score, so the states are solved
+/// by decreasing order (`a + q`) with every dice total summed directly, as
+/// for `HandicapSolver`, all three owners together.
+///
+/// # Example
+///
+/// ```rust
+/// use greed::{CubeOwner, CubeSolver, State};
+///
+/// let policy = CubeSolver::new(100, 6).policy();
+/// let action = policy.get(&State::new(90, 60, false), CubeOwner::Centered);
+/// println!("Double: {}, take: {}", action.double(), action.take());
+/// ```
+#[derive(Debug, Clone)]
+pub struct CubeSolver<T: Scalar = f64> {
+    /// Game configuration (maximum score and die sides).
+    ruleset: Ruleset,
+    /// Computed optimal policy.
+    policy: CubePolicy<T>,
+    /// Precomputed probability mass functions for dice rolls.
+    pmfs: PMFLookup<T>,
+}
+
+impl CubeSolver {
+    /// Create a new solver for the specified game parameters.
+    #[must_use]
+    pub fn new(max: u32, sides: u32) -> Self {
+        Self::from_ruleset(Ruleset::new(max, sides))
+    }
+    /// Create a new solver for a ruleset.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ruleset is not supported (see `try_from_ruleset`).
+    #[must_use]
+    pub fn from_ruleset(ruleset: Ruleset) -> Self {
+        Self::try_from_ruleset(ruleset).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Create a new solver for a ruleset, if it busts by losing and its ties
+    /// do not depend on the seat.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ruleset has soft busts (see `BustRule`), or
+    /// lets the second player win ties, which would make the seat part of the
+    /// state (see `TieRule`).
+    pub fn try_from_ruleset(ruleset: Ruleset) -> Result<Self, UnsupportedRuleset> {
+        if ruleset.bust_rule() != BustRule::Lose {
+            return Err(UnsupportedRuleset {
+                reason: "the doubling cube needs BustRule::Lose",
+            });
+        }
+        match ruleset.tie_payoff() {
+            Some(_) => Ok(Self::with_ruleset(ruleset)),
+            None => Err(UnsupportedRuleset {
+                reason: "the second player winning ties depends on the seat",
+            }),
+        }
+    }
+}
+
+impl<T: Scalar> CubeSolver<T> {
+    /// Create a new solver (computing in `T`) for a supported ruleset.
+    fn with_ruleset(ruleset: Ruleset) -> Self {
+        CubeSolver {
+            ruleset,
+            policy: CubePolicy::new(ruleset.max()),
+            pmfs: PMFLookup::default(),
+        }
+    }
+    /// Compute payoffs and probabilities in `U` instead.
+    #[must_use]
+    pub fn with_precision<U: Scalar>(self) -> CubeSolver<U> {
+        CubeSolver::with_ruleset(self.ruleset)
+    }
+    /// Returns the ruleset used by the solver.
+    #[must_use]
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+    /// Solve the game, and return its policy.
+    pub fn policy(&mut self) -> CubePolicy<T> {
+        self.solve();
+        self.policy.clone()
+    }
+    /// Consume the solver, returning its policy (as solved so far).
+    #[must_use]
+    pub fn into_policy(self) -> CubePolicy<T> {
+        self.policy
+    }
+    /// Compute the complete optimal policy, for every cube owner.
+    ///
+    /// Terminal states come first, then the normal states by decreasing
+    /// order. With `EndRule::BothStand`, a terminal state rolls back into
+    /// normal play, so the terminal states of each order are solved just
+    /// before its normal states instead, as for `HandicapSolver`.
+    ///
+    /// # Panics
+    ///
+    /// Panics (in release builds too) if any PMF's estimated error exceeds
+    /// `T::PMF_TOLERANCE`.
+    pub fn solve(&mut self) {
+        let max = self.ruleset.max();
+        self.pmfs = PMFLookup::precompute(max, self.ruleset.sides());
+
+        let end_rule = self.ruleset.end_rule();
+        if end_rule != EndRule::BothStand {
+            let solver = &*self;
+            let actions: Vec<(State, CubeOwner, CubeAction<T>)> = (0..=max)
+                .into_par_iter()
+                .flat_map_iter(|active| {
+                    (0..=max).flat_map(move |queued| {
+                        let state = State::new(active, queued, true);
+                        CubeOwner::ALL.map(|owner| match end_rule {
+                            EndRule::LastTurn => {
+                                (state, owner, solver.find_optimal_action(state, owner))
+                            }
+                            // The game is over.
+                            _ => {
+                                let payoff = solver.compare(state);
+                                (state, owner, CubeAction::new(0, false, false, payoff))
+                            }
+                        })
+                    })
+                })
+                .collect();
+            for (state, owner, action) in actions {
+                self.policy.set(&state, owner, action);
+            }
+        }
+
+        let layers: &[bool] = match end_rule {
+            EndRule::BothStand => &[true, false],
+            EndRule::LastTurn | EndRule::Immediate => &[false],
+        };
+        for order in (0..=2 * max).rev() {
+            for &last in layers {
+                let solver = &*self;
+                let actions: Vec<(State, CubeOwner, CubeAction<T>)> = (order.saturating_sub(max)
+                    ..=order.min(max))
+                    .into_par_iter()
+                    .flat_map_iter(|active| {
+                        let state = State::new(active, order - active, last);
+                        CubeOwner::ALL
+                            .map(|owner| (state, owner, solver.find_optimal_action(state, owner)))
+                    })
+                    .collect();
+                for (state, owner, action) in actions {
+                    self.policy.set(&state, owner, action);
+                }
+            }
+        }
+    }
+    /// The payoff of ending the game in `state`: comparing the scores (see
+    /// `TieRule`).
+    fn compare(&self, state: State) -> T {
+        match state.active().cmp(&state.queued()) {
+            Ordering::Less => -T::ONE,
+            Ordering::Equal => {
+                let tie = self.ruleset.tie_payoff();
+                T::from_f64(tie.expect("checked when the solver was created"))
+            }
+            Ordering::Greater => T::ONE,
+        }
+    }
+    /// Decide whether to double in `state` with the cube owned by `owner`,
+    /// whether the opponent takes, and how many dice to roll otherwise.
+    ///
+    /// Doubling and dropping are only chosen when they are better (up to
+    /// rounding).
+    ///
+    /// # Prerequisites
+    ///
+    /// All reachable future states must already be solved.
+    pub fn find_optimal_action(&self, state: State, owner: CubeOwner) -> CubeAction<T> {
+        let roll = self.optimal_roll(state, owner);
+        if !owner.can_double() {
+            return CubeAction::new(roll.n(), false, false, roll.payoff());
+        }
+        let tolerance = T::from_f64(T::TIE_TOLERANCE);
+        let taken = T::from_u32(2) * self.optimal_roll(state, CubeOwner::Opponent).payoff();
+        let take = taken <= T::ONE + tolerance;
+        let doubled = if take { taken } else { T::ONE };
+        match doubled > roll.payoff() + tolerance {
+            true => CubeAction::new(roll.n(), true, take, doubled),
+            false => CubeAction::new(roll.n(), false, take, roll.payoff()),
+        }
+    }
+    /// The largest number of dice worth considering (and allowed) in `state`,
+    /// as for `DpSolver`.
+    fn max_optimal_n(&self, state: State) -> u32 {
+        let (max, sides) = (self.ruleset.max(), self.ruleset.sides());
+        let max_optimal_n = 2 * (max - state.active() + sides) / (sides + 1);
+        self.ruleset
+            .max_dice()
+            .map_or(max_optimal_n, |max_dice| max_optimal_n.min(max_dice))
+    }
+    /// Find the number of dice (fewest among equals) with the best payoff in
+    /// `state` with the cube owned by `owner`, without doubling.
+    fn optimal_roll(&self, state: State, owner: CubeOwner) -> Action<T> {
+        let tolerance = T::from_f64(T::TIE_TOLERANCE);
+        let stand = Action::new(0, self.calc_payoff(state, owner, 0));
+        (1..=self.max_optimal_n(state)).fold(stand, |optimal, dice_rolled| {
+            let payoff = self.calc_payoff(state, owner, dice_rolled);
+            // If equal (up to rounding), the less aggressive move is taken.
+            if payoff > optimal.payoff() + tolerance {
+                Action::new(dice_rolled, payoff)
+            } else {
+                optimal
+            }
+        })
+    }
+    /// Calculate the expected payoff of rolling `dice_rolled` dice in
+    /// `state` with the cube owned by `owner`, by summing over every dice
+    /// total.
+    ///
+    /// # Prerequisites
+    ///
+    /// All reachable future states must already be solved.
+    #[must_use]
+    pub fn calc_payoff(&self, state: State, owner: CubeOwner, dice_rolled: u32) -> T {
+        let (max, sides) = (self.ruleset.max(), self.ruleset.sides());
+        let exact_win = self.ruleset.exact_win();
+        if state.last() && self.ruleset.end_rule() == EndRule::LastTurn {
+            if dice_rolled == 0 {
+                return self.compare(state);
+            }
+            return (dice_rolled..=sides * dice_rolled).fold(T::ZERO, |acc, dice_total| {
+                let probability = self.pmfs.lookup(dice_rolled, dice_total);
+                let score = state.active() + dice_total;
+                match score.cmp(&max) {
+                    Ordering::Greater => acc - probability, // bust
+                    Ordering::Equal if exact_win => acc + probability,
+                    _ => acc + probability * self.compare(State::new(score, state.queued(), true)),
+                }
+            });
+        }
+        // The opponent sees the cube from the other side.
+        let next_owner = owner.other();
+        if dice_rolled == 0 && state.last() {
+            return self.compare(state);
+        }
+        if dice_rolled == 0 {
+            let terminal = State::new(state.queued(), state.active(), true);
+            return -self.policy.get(&terminal, next_owner).payoff();
+        }
+        let headroom = max - state.active();
+        let bust = T::ONE - self.pmfs.cdf(dice_rolled, headroom);
+        (dice_rolled..=(sides * dice_rolled).min(headroom)).fold(-bust, |acc, dice_total| {
+            let probability = self.pmfs.lookup(dice_rolled, dice_total);
+            if exact_win && dice_total == headroom {
+                return acc + probability;
+            }
+            let next = State::new(state.queued(), state.active() + dice_total, false);
+            acc - probability * self.policy.get(&next, next_owner).payoff()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DpSolver, Solver, TieRule};
+
+    #[test]
+    fn test_final_round_doubles() {
+        let ruleset = Ruleset::new(30, 6);
+        let plain = DpSolver::from_ruleset(ruleset).policy();
+        let policy = CubeSolver::from_ruleset(ruleset).policy();
+        for (state, action) in plain.iter().filter(|(state, _)| state.last()) {
+            // Nobody rolls after the final roll, so only the mover's double
+            // matters: taken below 1/2, dropped above.
+            let roll = action.payoff();
+            let held = policy.get(&state, CubeOwner::Opponent);
+            assert!((held.payoff() - roll).abs() < 1e-12, "{state:?}");
+            for owner in [CubeOwner::Centered, CubeOwner::Mover] {
+                let cube = policy.get(&state, owner);
+                // A certain win gains nothing from a dropped double.
+                let gains = roll > 1e-12 && roll < 1.0 - 1e-12;
+                assert_eq!(cube.double(), gains, "{state:?} {owner}");
+                assert_eq!(cube.take(), roll <= 0.5 + 1e-12, "{state:?} {owner}");
+                let payoff = if cube.double() {
+                    (2.0 * roll).min(1.0)
+                } else {
+                    roll
+                };
+                assert!((cube.payoff() - payoff).abs() < 1e-12, "{state:?} {owner}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_owning_the_cube_helps() {
+        for end_rule in EndRule::ALL {
+            let ruleset = Ruleset::new(24, 4)
+                .with_end_rule(end_rule)
+                .with_exact_win(true);
+            let policy = CubeSolver::from_ruleset(ruleset).policy();
+            for (state, owner, action) in policy.iter() {
+                assert_eq!(policy.get(&state, owner).payoff(), action.payoff());
+                assert!(
+                    action.payoff().abs() <= 1.0 + 1e-12,
+                    "{end_rule} {state:?} {owner}"
+                );
+                if owner == CubeOwner::Centered {
+                    let mover = policy.get(&state, CubeOwner::Mover).payoff();
+                    let opponent = policy.get(&state, CubeOwner::Opponent).payoff();
+                    let payoff = action.payoff();
+                    assert!(mover >= payoff - 1e-12, "{end_rule} {state:?}");
+                    assert!(payoff >= opponent - 1e-12, "{end_rule} {state:?}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_unsupported_rulesets() {
+        let ruleset = Ruleset::new(30, 6);
+        assert!(CubeSolver::try_from_ruleset(ruleset.with_max_dice(3)).is_ok());
+        for unsupported in [
+            ruleset.with_bust_rule(BustRule::Bounce),
+            ruleset.with_tie_rule(TieRule::SecondPlayer),
+        ] {
+            assert!(CubeSolver::try_from_ruleset(unsupported).is_err());
+        }
+    }
+}
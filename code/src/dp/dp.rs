@@ -1,10 +1,41 @@
 use std::cmp::Ordering;
+use std::io;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use rayon::prelude::*;
 
-use super::pmf::fft_convolve;
-use crate::{Action, Policy, Ruleset, Solver, State};
+use super::checkpoint;
+use super::pmf::{PmfBackend, PmfError, estimate_error, neumaier};
+use super::progress::{CancelToken, Phase, Progress, SolveError};
+use super::terminal::{TerminalTable, find_optimal_terminal_action};
+use super::wavefront::{self, Tile};
+use crate::{
+    Action, BustRule, EndRule, Policy, Ruleset, Scalar, Solver, State, UnsupportedRuleset,
+};
+
+/// The largest estimated PMF error tolerated by `PMFLookup::precompute` (for
+/// `f64`; see `Scalar::PMF_TOLERANCE`).
+pub const PMF_TOLERANCE: f64 = <f64 as Scalar>::PMF_TOLERANCE;
+
+/// Returns an error for the first PMF whose estimated error exceeds
+/// `tolerance`.
+fn check_errors(errors: &[f64], backend: PmfBackend, tolerance: f64) -> Result<(), PmfError> {
+    match errors
+        .iter()
+        .position(|&error| error.is_nan() || error > tolerance)
+    {
+        Some(n) => Err(PmfError {
+            backend,
+            n: n as u32,
+            error: errors[n],
+            tolerance,
+        }),
+        None => Ok(()),
+    }
+}
 
 /// Optimized lookup table for dice roll probability mass functions.
 ///
@@ -12,27 +43,41 @@ use crate::{Action, Policy, Ruleset, Solver, State};
 /// O(1) lookup of P(sum = k | n dice). This is the performance-critical
 /// component of the solver, as PMF lookups occur millions of times during
 /// policy computation.
+///
+/// The matching cumulative distributions are stored alongside, so that the
+/// probability of any range of totals is also an O(1) lookup.
+///
+/// Probabilities are stored as a `Scalar` (`f64` unless otherwise specified).
 #[derive(Debug, Clone)]
-pub struct PMFLookup {
+pub struct PMFLookup<T: Scalar = f64> {
     /// Flat array containing all PMF data.
-    data: Box<[f64]>,
+    data: Box<[T]>,
+    /// Flat array containing all CDF data, laid out like `data`.
+    cdf: Box<[T]>,
     /// Starting offsets for each n-dice PMF.
     offsets: Box<[usize]>,
+    /// Estimated maximum absolute error of each n-dice PMF.
+    errors: Box<[f64]>,
     /// Maximum number of dice.
     max_n: u32,
+    /// The number of sides on each die.
+    sides: u32,
 }
 
-impl Default for PMFLookup {
+impl<T: Scalar> Default for PMFLookup<T> {
     fn default() -> Self {
         Self {
             data: Box::new([]),
+            cdf: Box::new([]),
             offsets: Box::new([]),
+            errors: Box::new([]),
             max_n: 0,
+            sides: 0,
         }
     }
 }
 
-impl PMFLookup {
+impl<T: Scalar> PMFLookup<T> {
     /// Precompute all required PMFs for the given game parameters.
     ///
     /// Generates PMFs for 0 to max_n dice, where max_n is determined by the
@@ -43,33 +88,34 @@ impl PMFLookup {
     /// # Time Complexity
     ///
     /// O(max_n × sides × log(sides)) due to FFT operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics (in release builds too) if any PMF's estimated error exceeds
+    /// `T::PMF_TOLERANCE`.
     #[must_use]
     pub fn precompute(max: u32, sides: u32) -> Self {
+        Self::precompute_with(max, sides, PmfBackend::Fft, T::PMF_TOLERANCE)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Precompute all required PMFs for the given game parameters with the
+    /// chosen backend.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any PMF's estimated maximum absolute error (see
+    /// `errors`) exceeds `tolerance`.
+    pub fn precompute_with(
+        max: u32,
+        sides: u32,
+        backend: PmfBackend,
+        tolerance: f64,
+    ) -> Result<Self, PmfError> {
         let max_n = (2 * (max + sides) / (sides + 1)).max(max + 1);
-        let dice_pmf = vec![1.0 / f64::from(sides); sides as usize];
-
-        // First pass: compute individual PMFs to determine total size
-        let mut temp_pmfs: Vec<Vec<f64>> = Vec::with_capacity((max_n + 1) as usize);
-        temp_pmfs.push(vec![1.0]); // n=0 case
+        let (temp_pmfs, errors) = backend.generate(max_n, sides);
+        check_errors(&errors, backend, tolerance)?;
 
-        for n in 1..=max_n {
-            temp_pmfs.push(fft_convolve(&temp_pmfs[(n - 1) as usize], &dice_pmf));
-        }
-
-        // Validate PMFs sum to 1.0
-        for (n, pmf) in temp_pmfs.iter().enumerate() {
-            if n > 0 {
-                let sum: f64 = pmf.iter().sum();
-                debug_assert!(
-                    (sum - 1.0).abs() < 1e-10,
-                    "PMF for {} dice doesn't sum to 1.0: {}",
-                    n,
-                    sum
-                );
-            }
-        }
-
-        // Second pass: flatten into single array with offset table
+        // Flatten into single array with offset table
         let total_size: usize = temp_pmfs.iter().map(|v| v.len()).sum();
         let mut data = Vec::with_capacity(total_size);
         let mut offsets = Vec::with_capacity((max_n + 1) as usize);
@@ -79,10 +125,91 @@ impl PMFLookup {
             data.extend_from_slice(pmf);
         }
 
+        Ok(Self::from_rows(data, offsets, errors, max_n, sides))
+    }
+    /// Precompute PMFs for 0 to `max_n` dice, keeping only totals ≤ `max`.
+    ///
+    /// Totals above `max` always bust, so their probabilities are only ever
+    /// needed in aggregate (as one minus the stored mass). Each PMF is built
+    /// from the previous one by a direct (compensated) sum over the die faces,
+    /// which avoids materializing the untruncated tables.
+    ///
+    /// Complete rows are checked like `precompute`'s; truncated rows can only
+    /// be checked for holding more than all of the probability mass.
+    ///
+    /// # Space Complexity
+    ///
+    /// O(max_n × min(max_n × sides, max)).
+    ///
+    /// # Panics
+    ///
+    /// Panics (in release builds too) if any PMF's estimated error exceeds
+    /// `T::PMF_TOLERANCE`.
+    #[must_use]
+    pub fn truncated(max: u32, sides: u32, max_n: u32) -> Self {
+        let mut data = vec![T::ONE]; // n=0 case
+        let mut offsets = vec![0];
+        let mut errors = vec![0.0];
+        let mut previous = 0..1;
+
+        for n in 1..=u64::from(max_n) {
+            // Totals n..=min(n * sides, max), stored from index `offset`.
+            let complete = n * u64::from(sides) <= u64::from(max);
+            let len = (n * u64::from(sides))
+                .min(u64::from(max))
+                .saturating_sub(n - 1) as usize;
+            let offset = data.len();
+            offsets.push(offset);
+
+            // P(n, t) = (1 / sides) * Σ_{k=1..=sides} P(n - 1, t - k), where
+            // index i of this row is total n + i and index j of the previous
+            // row is total n - 1 + j, so t - k lands on j = i + 1 - k.
+            for i in 0..len {
+                let hi = i.min(previous.len() - 1);
+                let lo = (i + 1).saturating_sub(sides as usize);
+                let window = neumaier(&data[previous.start + lo..=previous.start + hi]);
+                data.push(window / T::from_u32(sides));
+            }
+            previous = offset..offset + len;
+
+            let row = &data[previous.clone()];
+            errors.push(if complete {
+                estimate_error(row)
+            } else {
+                (neumaier(row) - T::ONE).to_f64().max(0.0)
+            });
+        }
+        check_errors(&errors, PmfBackend::Compensated, T::PMF_TOLERANCE)
+            .unwrap_or_else(|error| panic!("{error}"));
+
+        Self::from_rows(data, offsets, errors, max_n, sides)
+    }
+    /// Build the lookup table (and its cumulative distributions) from
+    /// flattened PMF rows.
+    fn from_rows(
+        data: Vec<T>,
+        offsets: Vec<usize>,
+        errors: Vec<f64>,
+        max_n: u32,
+        sides: u32,
+    ) -> Self {
+        let mut cdf = Vec::with_capacity(data.len());
+        for (n, &start) in offsets.iter().enumerate() {
+            let end = offsets.get(n + 1).copied().unwrap_or(data.len());
+            let mut cumulative = T::ZERO;
+            for &probability in &data[start..end] {
+                cumulative += probability;
+                cdf.push(cumulative);
+            }
+        }
+
         Self {
             data: data.into_boxed_slice(),
+            cdf: cdf.into_boxed_slice(),
             offsets: offsets.into_boxed_slice(),
+            errors: errors.into_boxed_slice(),
             max_n,
+            sides,
         }
     }
     /// Fast lookup of PMF value P(sum = total | n dice).
@@ -96,7 +223,7 @@ impl PMFLookup {
     /// Caller must ensure n ≤ max_n and total ≥ n.
     #[must_use]
     #[inline]
-    pub fn lookup(&self, n: u32, total: u32) -> f64 {
+    pub fn lookup(&self, n: u32, total: u32) -> T {
         debug_assert!(n <= self.max_n, "n={} exceeds max_n={}", n, self.max_n);
         debug_assert!(total >= n, "total={} less than n={}", total, n);
 
@@ -113,29 +240,177 @@ impl PMFLookup {
     /// code. Slightly slower than `lookup()` due to bounds checking.
     #[must_use]
     #[inline]
-    pub fn lookup_safe(&self, n: u32, total: u32) -> f64 {
+    pub fn lookup_safe(&self, n: u32, total: u32) -> T {
         if n > self.max_n || total < n {
-            return 0.0;
+            return T::ZERO;
         }
 
         let offset = self.offsets[n as usize];
+        let end = self
+            .offsets
+            .get(n as usize + 1)
+            .copied()
+            .unwrap_or(self.data.len());
         let index = offset + (total - n) as usize;
 
-        if index < self.data.len() {
+        if index < end {
             self.data[index]
         } else {
-            0.0
+            T::ZERO
         }
     }
+    /// Lookup of the CDF value P(sum ≤ total | n dice).
+    ///
+    /// Totals below `n` have probability 0 and totals of at least `n × sides`
+    /// have probability 1. For tables built with `truncated`, other totals
+    /// must not exceed the truncation point.
+    #[must_use]
+    #[inline]
+    pub fn cdf(&self, n: u32, total: u32) -> T {
+        debug_assert!(n <= self.max_n, "n={} exceeds max_n={}", n, self.max_n);
+
+        if total < n {
+            T::ZERO
+        } else if u64::from(total) >= u64::from(n) * u64::from(self.sides) {
+            T::ONE
+        } else {
+            self.cdf[self.offsets[n as usize] + (total - n) as usize]
+        }
+    }
+    /// Probability that the sum of `n` dice lies in `lo..=hi`.
+    ///
+    /// Empty ranges (`lo > hi`) have probability 0.
+    #[must_use]
+    #[inline]
+    pub fn probability_between(&self, n: u32, lo: u32, hi: u32) -> T {
+        if lo > hi {
+            return T::ZERO;
+        }
+        let below = if lo == 0 {
+            T::ZERO
+        } else {
+            self.cdf(n, lo - 1)
+        };
+        let probability = self.cdf(n, hi) - below;
+        if probability > T::ZERO {
+            probability
+        } else {
+            T::ZERO
+        }
+    }
+    /// Returns the maximum number of dice covered by the lookup table.
+    #[must_use]
+    pub fn max_n(&self) -> u32 {
+        self.max_n
+    }
+    /// Returns the number of sides on each die.
+    #[must_use]
+    pub fn sides(&self) -> u32 {
+        self.sides
+    }
+    /// Returns the estimated maximum absolute error of the PMF of each number
+    /// of dice (indexed by `n`).
+    ///
+    /// The exact PMFs sum to 1 and are symmetric, so the estimate is the
+    /// largest violation of either property (or, for FFT, the largest negative
+    /// value clamped to 0).
+    #[must_use]
+    pub fn errors(&self) -> &[f64] {
+        &self.errors
+    }
     /// Returns memory usage statistics for the PMF lookup table.
     #[must_use]
     pub fn memory_usage(&self) -> (usize, usize) {
-        let data_bytes = self.data.len() * std::mem::size_of::<f64>();
+        let data_bytes = (self.data.len() + self.cdf.len()) * std::mem::size_of::<T>();
         let offset_bytes = self.offsets.len() * std::mem::size_of::<usize>();
         (data_bytes, offset_bytes)
     }
 }
 
+/// The fewest orders a tile holds by default (see `DpSolver::with_tile_height`).
+pub const MIN_TILE_HEIGHT: u32 = 4;
+
+/// The most sweeps of value iteration with a soft `BustRule`, per die side.
+///
+/// Every sweep shrinks the change by roughly the chance of a roll staying on
+/// the board, which can be as low as `1 / sides`, so the sweeps needed grow
+/// with the sides: about 25 per side in `f64` and 60 per side in
+/// `DoubleDouble` on the slowest boards tried.
+pub const SWEEPS_PER_SIDE: u32 = 200;
+
+/// The batched payoffs `Wⁿ(x)` of every opponent score (see
+/// `DpSolver::solve_normal_states`).
+#[derive(Debug, Clone)]
+pub(super) struct Windows<T: Scalar> {
+    /// One window per opponent score.
+    pub(super) rows: Vec<Window<T>>,
+}
+
+/// The batched payoffs `Wⁿ(x)` for a single opponent score.
+///
+/// Only the `sides + 1` most recently reached scores are needed, so the window
+/// is a ring buffer where score `x` lives in slot `x % (sides + 1)`.
+#[derive(Debug, Clone)]
+pub(super) struct Window<T: Scalar> {
+    /// `data[slot * (max_n + 1) + n]` = Wⁿ(x) for the score `x` in `slot`.
+    data: Box<[T]>,
+    /// The largest number of dice considered in any state.
+    max_n: usize,
+}
+
+impl<T: Scalar> Windows<T> {
+    /// Create the windows for every opponent score, with the scores past the
+    /// maximum (which always bust) already filled in.
+    pub(super) fn new(max: u32, sides: u32) -> Self {
+        let max_n = (2 * (max + sides) / (sides + 1)) as usize;
+        let window = Window {
+            data: vec![-T::ONE; (sides as usize + 1) * (max_n + 1)].into_boxed_slice(),
+            max_n,
+        };
+        Self {
+            rows: vec![window; max as usize + 1],
+        }
+    }
+    /// Returns the largest number of dice considered in any state.
+    pub(super) fn width(&self) -> usize {
+        self.rows.first().map_or(0, |window| window.max_n)
+    }
+    /// Iterate over every stored payoff, window by window.
+    pub(super) fn values(&self) -> impl Iterator<Item = &T> {
+        self.rows.iter().flat_map(|window| window.data.iter())
+    }
+    /// Iterate over every stored payoff mutably, window by window.
+    pub(super) fn values_mut(&mut self) -> impl Iterator<Item = &mut T> {
+        self.rows
+            .iter_mut()
+            .flat_map(|window| window.data.iter_mut())
+    }
+}
+
+impl<T: Scalar> Window<T> {
+    /// The slot of the score `x`.
+    fn slot(&self, x: u32) -> usize {
+        x as usize % (self.data.len() / (self.max_n + 1))
+    }
+    /// Get Wⁿ(x) for every `n`.
+    pub(super) fn payoffs(&self, x: u32) -> &[T] {
+        let start = self.slot(x) * (self.max_n + 1);
+        &self.data[start..start + self.max_n + 1]
+    }
+    /// Get Wⁿ(x) for every `n`, mutably.
+    pub(super) fn payoffs_mut(&mut self, x: u32) -> &mut [T] {
+        let start = self.slot(x) * (self.max_n + 1);
+        &mut self.data[start..start + self.max_n + 1]
+    }
+    /// Compute Wⁿ(x) for every `n` ≥ 1 from the `sides` scores above `x`.
+    pub(super) fn extend(&mut self, x: u32, sides: u32) {
+        for n in 1..=self.max_n {
+            let sum: T = (1..=sides).map(|k| self.payoffs(x + k)[n - 1]).sum();
+            self.payoffs_mut(x)[n] = sum / T::from_u32(sides);
+        }
+    }
+}
+
 /// Computes optimal strategies for Greed using dynamic programming.
 ///
 /// The solver determines the best action (number of dice to roll) for every
@@ -157,34 +432,196 @@ impl PMFLookup {
 /// States are processed in reverse order of total score (active + queued) to
 /// ensure all future states are already computed when needed.
 ///
+/// # Precision
+///
+/// Payoffs and probabilities are computed in a `Scalar`, `f64` by default.
+/// Use `with_precision` to solve in `f32` (half the memory) or
+/// `DoubleDouble` (about twice the precision).
+///
 /// # Example
 ///
 /// ```rust
-/// let mut solver = GreedSolver::new(100, 6);
-/// solver.solve();
-/// let action = solver.policy.get(&State::new(50, 45, false));
-/// println!("Optimal: roll {} dice (payoff: {:.3})", action.n, action.payoff);
+/// use greed::{DpSolver, Solver, State};
+///
+/// let mut solver = DpSolver::new(100, 6);
+/// let policy = solver.policy();
+/// let action = policy.get(&State::new(50, 45, false));
+/// println!("Optimal: roll {} dice (payoff: {:.3})", action.n(), action.payoff());
 /// ```
 #[derive(Debug, Clone, Default)]
-pub struct DpSolver {
+pub struct DpSolver<T: Scalar = f64> {
     /// Game configuration (maximum score and die sides).
     ruleset: Ruleset,
     /// Computed optimal policy mapping states to actions.
-    policy: Policy,
+    policy: Policy<T>,
     /// Precomputed probability mass functions for dice rolls.
-    pmfs: PMFLookup,
+    pmfs: PMFLookup<T>,
+    /// Optimal terminal actions, possibly shared with other solvers.
+    terminal: Option<Arc<TerminalTable<T>>>,
+    /// The algorithm used to generate the PMFs.
+    pmf_backend: PmfBackend,
+    /// Where, and how often, to checkpoint the normal states.
+    checkpoints: Option<(PathBuf, Duration)>,
+    /// The next order to solve and the batched payoffs so far, when resuming
+    /// from a checkpoint.
+    resume: Option<(u32, Windows<T>)>,
+    /// The number of orders per tile of normal states (automatic if `None`).
+    tile_height: Option<u32>,
 }
 
 impl DpSolver {
     /// Create a new solver for the specified game parameters.
     #[must_use]
     pub fn new(max: u32, sides: u32) -> Self {
+        Self::with_ruleset(Ruleset::new(max, sides))
+    }
+    /// Create a new solver for a ruleset (including its end rule).
+    ///
+    /// The komi only changes where the game starts, so it does not affect
+    /// the policy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ruleset lets the second player win ties (see
+    /// `try_from_ruleset`).
+    #[must_use]
+    pub fn from_ruleset(ruleset: Ruleset) -> Self {
+        Self::try_from_ruleset(ruleset).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Create a new solver for a ruleset, if its ties do not depend on the
+    /// seat.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ruleset lets the second player win ties (see
+    /// `TieRule`), which `HandicapSolver` solves instead.
+    pub fn try_from_ruleset(ruleset: Ruleset) -> Result<Self, UnsupportedRuleset> {
+        match ruleset.tie_payoff() {
+            Some(_) => Ok(Self::with_ruleset(ruleset)),
+            None => Err(UnsupportedRuleset {
+                reason: "the second player winning ties depends on the seat; use HandicapSolver",
+            }),
+        }
+    }
+}
+
+impl<T: Scalar> DpSolver<T> {
+    /// Create a new solver (computing in `T`) for a ruleset whose ties do not
+    /// depend on the seat.
+    fn with_ruleset(ruleset: Ruleset) -> Self {
         DpSolver {
-            ruleset: Ruleset::new(max, sides),
-            policy: Policy::new(max),
+            ruleset,
+            policy: Policy::new(ruleset.max()),
             pmfs: PMFLookup::default(),
+            terminal: None,
+            pmf_backend: PmfBackend::default(),
+            checkpoints: None,
+            resume: None,
+            tile_height: None,
+        }
+    }
+    /// Compute payoffs and probabilities in `U` instead.
+    ///
+    /// Anything already computed (including a shared terminal table) is
+    /// discarded.
+    #[must_use]
+    pub fn with_precision<U: Scalar>(self) -> DpSolver<U> {
+        let (ruleset, pmf_backend, tile_height) =
+            (self.ruleset, self.pmf_backend, self.tile_height);
+        drop(self);
+        DpSolver {
+            pmf_backend,
+            tile_height,
+            ..DpSolver::with_ruleset(ruleset)
+        }
+    }
+    /// Generate the PMFs with `backend` instead of FFT.
+    #[must_use]
+    pub fn with_pmf_backend(self, backend: PmfBackend) -> Self {
+        Self {
+            pmf_backend: backend,
+            ..self
+        }
+    }
+    /// End the game according to `end_rule` instead.
+    pub(super) fn with_end_rule(self, end_rule: EndRule) -> Self {
+        Self {
+            ruleset: self.ruleset.with_end_rule(end_rule),
+            ..self
+        }
+    }
+    /// Reuse an already solved terminal table instead of solving one.
+    ///
+    /// The table is only used if it covers this ruleset (same dice, and a
+    /// headroom of at least the maximum score), its end rule is
+    /// `EndRule::LastTurn`, its bust rule is `BustRule::Lose`, landing
+    /// exactly on the maximum does not win outright, the dice are not
+    /// limited, and a tie is worth nothing.
+    #[must_use]
+    pub fn with_terminal_table(self, terminal: Arc<TerminalTable<T>>) -> Self {
+        Self {
+            terminal: Some(terminal),
+            ..self
+        }
+    }
+    /// Solve the normal states in tiles of `orders` orders.
+    ///
+    /// Progress, cancellation and checkpoints are only handled between tiles,
+    /// so smaller tiles respond faster but synchronize more often. By default
+    /// a tile holds `√(2 × max + 1)` orders (at least `MIN_TILE_HEIGHT`), so
+    /// a solve reports about as many steps as a tile has orders.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `orders` is 0.
+    #[must_use]
+    pub fn with_tile_height(self, orders: u32) -> Self {
+        assert!(orders > 0, "a tile must hold at least one order");
+        Self {
+            tile_height: Some(orders),
+            ..self
         }
     }
+    /// Periodically save the progress of the normal states to `path`.
+    ///
+    /// A checkpoint is written after the first tile of orders that finishes
+    /// at least `every` after the previous one, and when the solve is
+    /// cancelled. See `resume_from`.
+    ///
+    /// Soft busts (see `BustRule`) are never checkpointed, so solving them
+    /// with checkpoints fails with `SolveError::Checkpoint`.
+    #[must_use]
+    pub fn with_checkpoints(self, path: impl Into<PathBuf>, every: Duration) -> Self {
+        Self {
+            checkpoints: Some((path.into(), every)),
+            ..self
+        }
+    }
+    /// Continue from a checkpoint written by a solver with the same ruleset,
+    /// precision and PMF backend.
+    ///
+    /// The terminal states and the solved orders are loaded, and the next
+    /// solve only computes the PMFs and the remaining orders.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the checkpoint cannot be read, or was written for
+    /// a different ruleset, precision or PMF backend, or if the ruleset has
+    /// soft busts.
+    pub fn resume_from(self, path: impl AsRef<Path>) -> io::Result<Self> {
+        let checkpoint = checkpoint::read(path.as_ref(), self.ruleset, self.pmf_backend)?;
+        Ok(Self {
+            policy: checkpoint.policy,
+            resume: Some((checkpoint.order, checkpoint.windows)),
+            ..self
+        })
+    }
+    /// Returns the terminal table used by the solver, if it has been solved
+    /// (or provided).
+    #[must_use]
+    pub fn terminal_table(&self) -> Option<Arc<TerminalTable<T>>> {
+        self.terminal.clone()
+    }
     /// Precompute probability mass functions for all strategically relevant
     /// dice counts.
     ///
@@ -196,21 +633,95 @@ impl DpSolver {
     ///
     /// This is a one-time cost that dramatically speeds up the subsequent solve
     /// operations.
+    ///
+    /// # Panics
+    ///
+    /// Panics (in release builds too) if any PMF's estimated error exceeds
+    /// `T::PMF_TOLERANCE`.
     pub fn precompute_pmfs(&mut self) {
-        self.pmfs = PMFLookup::precompute(self.max(), self.sides());
+        self.pmfs = PMFLookup::precompute_with(
+            self.max(),
+            self.sides(),
+            self.pmf_backend,
+            T::PMF_TOLERANCE,
+        )
+        .unwrap_or_else(|error| panic!("{error}"));
     }
+
     /// Compute the complete optimal policy for this game configuration.
     ///
     /// Performs the full two-stage solve: terminal states first, then normal
     /// states. After completion, the policy can be queried for any valid game
     /// state.
+    ///
+    /// # Panics
+    ///
+    /// Panics if soft busts do not converge (see `solve_with`).
     pub fn solve(&mut self) {
+        self.solve_with(|_| {}, &CancelToken::new())
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+    /// Compute the complete optimal policy, reporting progress after every
+    /// step and stopping early if `cancel` is cancelled.
+    ///
+    /// The token is checked between phases and between orders of the normal
+    /// phase. A cancelled solve leaves the policy partially solved.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolveError::Cancelled` if the token was cancelled before the
+    /// solve finished, `SolveError::Checkpoint` if a checkpoint could not be
+    /// written or checkpoints were asked for with a soft `BustRule`, or
+    /// `SolveError::NotConverged` if a soft `BustRule` did not converge.
+    pub fn solve_with(
+        &mut self,
+        mut progress: impl FnMut(Progress),
+        cancel: &CancelToken,
+    ) -> Result<(), SolveError> {
+        if self.checkpoints.is_some() && self.ruleset.bust_rule() != BustRule::Lose {
+            return Err(SolveError::Checkpoint(io::Error::new(
+                io::ErrorKind::Unsupported,
+                "soft busts cannot be checkpointed",
+            )));
+        }
+        let orders = 2 * self.max();
+        let check_cancelled = || match cancel.is_cancelled() {
+            true => Err(SolveError::Cancelled),
+            false => Ok(()),
+        };
+
         // Precompute all PMFs
+        check_cancelled()?;
+        let start = Instant::now();
         self.precompute_pmfs();
-        // Solve all the terminal states (this must be done first).
-        self.solve_terminal_states();
+        progress(Progress::new(
+            Phase::Pmfs,
+            None,
+            orders,
+            (1, 1),
+            start.elapsed(),
+        ));
+        // Solve all the terminal states (this must be done first), unless they
+        // were loaded from a checkpoint.
+        check_cancelled()?;
+        let start = Instant::now();
+        if self.resume.is_none() {
+            self.solve_terminal_states();
+        }
+        progress(Progress::new(
+            Phase::Terminal,
+            None,
+            orders,
+            (1, 1),
+            start.elapsed(),
+        ));
         // Solve all the normal states (in the correct order).
-        self.solve_normal_states();
+        self.solve_normal_states_with(progress, cancel)
+    }
+    /// Consume the solver, returning its policy (as solved so far).
+    #[must_use]
+    pub fn into_policy(self) -> Policy<T> {
+        self.policy
     }
     /// Returns the maximum score for this game configuration.
     #[must_use]
@@ -224,25 +735,72 @@ impl DpSolver {
     }
 }
 
-impl DpSolver {
+impl<T: Scalar> DpSolver<T> {
     /// Compute optimal actions for all terminal (final round) states.
     ///
     /// Terminal states occur when one player has stood, triggering the final
     /// round. These states can be solved independently since there are no
     /// future rounds to consider.
+    ///
+    /// Since a terminal state only depends on its gap and headroom, the
+    /// actions are read from a `TerminalTable`. A table provided with
+    /// `with_terminal_table` (or solved earlier) is reused when it covers this
+    /// ruleset; otherwise one is solved for this maximum score.
+    ///
+    /// The other end rules have no such final turn. With `EndRule::Immediate`
+    /// a terminal state is a finished game, so it stands with the payoff of
+    /// comparing scores. With `EndRule::BothStand` a terminal state can roll
+    /// back into normal play, so terminal states are solved together with the
+    /// normal states instead.
+    ///
+    /// The table assumes that busting loses, that landing on the maximum is
+    /// just a high score, that any number of dice may be rolled, and that a
+    /// tie is worth nothing. Otherwise (see `BustRule`,
+    /// `Ruleset::with_exact_win`, `Ruleset::with_max_dice` and `TieRule`),
+    /// every terminal state is searched directly instead.
     pub fn solve_terminal_states(&mut self) {
-        let states: Vec<_> = (0..=self.max())
-            .flat_map(|turn| (0..=self.max()).map(move |next| State::new(turn, next, true)))
-            .collect();
-
-        let actions: Vec<_> = states
-            .par_iter()
-            .map(|state| (*state, self.find_optimal_terminal_action(*state)))
-            .collect();
+        match self.ruleset.end_rule() {
+            EndRule::LastTurn if !self.standard_terminal() => {
+                let solver = &*self;
+                let actions: Vec<(State, Action<T>)> = (0..=self.max())
+                    .into_par_iter()
+                    .flat_map_iter(|turn| {
+                        (0..=solver.max()).map(move |next| {
+                            let state = State::new(turn, next, true);
+                            (state, solver.find_optimal_terminal_action(state))
+                        })
+                    })
+                    .collect();
+                for (state, action) in actions {
+                    self.policy.set(&state, action);
+                }
+                return;
+            }
+            EndRule::LastTurn => {}
+            EndRule::Immediate => {
+                for turn in 0..=self.max() {
+                    for next in 0..=self.max() {
+                        let state = State::new(turn, next, true);
+                        let payoff = self.calc_terminal_payoff(state, 0);
+                        self.policy.set(&state, Action::new(0, payoff));
+                    }
+                }
+                return;
+            }
+            EndRule::BothStand => return,
+        }
+        let table = match &self.terminal {
+            Some(table) if table.covers(self.max(), self.sides()) => table.clone(),
+            _ => Arc::new(TerminalTable::solve(self.max(), &self.pmfs)),
+        };
 
-        for (state, action) in actions {
-            self.policy.set(&state, action);
+        for turn in 0..=self.max() {
+            for next in 0..=self.max() {
+                let state = State::new(turn, next, true);
+                self.policy.set(&state, table.get(&state, self.max()));
+            }
         }
+        self.terminal = Some(table);
     }
     /// Find the optimal number of dice to roll in a terminal state.
     ///
@@ -255,34 +813,41 @@ impl DpSolver {
     /// + Handle obvious cases (already winning, guaranteed win scenarios)
     /// + Search from minimum viable dice count upward
     /// + Stop when payoff decreases consistently or search limit reached
-    pub fn find_optimal_terminal_action(&self, state: State) -> Action {
-        if state.active() > state.queued() {
-            // If already ahead, doing nothing wins 100% of the time.
-            return Action { n: 0, payoff: 1.0 };
-        }
-        if self.sides() * (state.queued() - state.active() + 1) <= self.max() - state.active() {
-            // If there is some action A where the minimum sum > queued - active AND the
-            // maximum sum is < max score - active, then that action wins 100% of the time.
-            return Action::new(state.queued() - state.active() + 1, 1.0);
+    ///
+    /// Soft busts (see `BustRule`) break unimodality, and neither an
+    /// exact-maximum win nor a tie that is not a draw (see `TieRule`) is
+    /// covered by the search, so then every dice count up to the same limit
+    /// as for normal states is tried.
+    pub fn find_optimal_terminal_action(&self, state: State) -> Action<T> {
+        if self.ruleset.bust_rule() != BustRule::Lose
+            || self.ruleset.exact_win()
+            || self.ruleset.tie_payoff() != Some(0.0)
+        {
+            let (optimal_roll, optimal_payoff) = (0..=self.max_optimal_n(state))
+                .rev() // If equal, the less aggressive move is taken.
+                .map(|dice_rolled| (dice_rolled, self.calc_terminal_payoff(state, dice_rolled)))
+                .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
+                .unwrap();
+            return Action::new(optimal_roll, optimal_payoff);
         }
-
-        let mut optimal_action = Action::new(0, -1.0);
-        let mut dice_rolled = (state.queued() - state.active()) / self.sides(); // Start at min non-zero payoff.
-
-        loop {
-            let current_payoff = self.calc_terminal_payoff(state, dice_rolled);
-            if optimal_action.payoff - current_payoff >= 10e-2
-                || dice_rolled >= (2 * self.max() / (self.sides() + 1) + 1).max(self.max() + 1)
-            {
-                break;
-            }
-            if current_payoff > optimal_action.payoff {
-                optimal_action = Action::new(dice_rolled, current_payoff);
-            }
-            dice_rolled += 1;
+        match state.queued().checked_sub(state.active()) {
+            Some(gap) => find_optimal_terminal_action(
+                gap,
+                self.max() - state.active(),
+                self.ruleset.max_dice(),
+                &self.pmfs,
+            ),
+            // If already ahead, doing nothing wins 100% of the time.
+            None => Action::new(0, T::ONE),
         }
-
-        optimal_action
+    }
+    /// Returns whether the terminal states follow the standard payoffs that
+    /// `TerminalTable` is solved for.
+    fn standard_terminal(&self) -> bool {
+        self.ruleset.bust_rule() == BustRule::Lose
+            && !self.ruleset.exact_win()
+            && self.ruleset.max_dice().is_none()
+            && self.ruleset.tie_payoff() == Some(0.0)
     }
     /// Calculate expected payoff for rolling a specific number of dice in a
     /// terminal state.
@@ -291,34 +856,128 @@ impl DpSolver {
     /// sums:
     /// - Win: final score > opponent's score and ≤ max
     /// - Lose: final score < opponent's score or > max (bust)
-    /// - Tie: final score = opponent's score
-    pub fn calc_terminal_payoff(&self, state: State, dice_rolled: u32) -> f64 {
+    /// - Tie: final score = opponent's score (see `TieRule`)
+    ///
+    /// Only three probabilities are needed, P(win), P(tie), and everything
+    /// else, so the payoff is read off the CDF in O(1). With a soft
+    /// `BustRule`, busting totals are scored by where they land instead (see
+    /// `calc_terminal_bust_payoff`).
+    pub fn calc_terminal_payoff(&self, state: State, dice_rolled: u32) -> T {
         if dice_rolled == 0 {
             return match state.active().cmp(&state.queued()) {
-                Ordering::Less => -1.0,
-                Ordering::Equal => 0.0,
-                Ordering::Greater => 1.0,
+                Ordering::Less => -T::ONE,
+                Ordering::Equal => self.tie_payoff(),
+                Ordering::Greater => T::ONE,
             };
         }
 
-        (dice_rolled..=self.sides() * dice_rolled).fold(0.0, |acc, dice_total| {
+        let headroom = self.max() - state.active();
+        // Totals in `lo..=headroom` win, and a total of `lo - 1` ties (if the
+        // opponent is not behind).
+        let (lo, tie) = match state.queued().checked_sub(state.active()) {
+            Some(gap) => (gap + 1, self.pmfs.lookup_safe(dice_rolled, gap)),
+            None => (0, T::ZERO),
+        };
+        let win = self.pmfs.probability_between(dice_rolled, lo, headroom);
+        // Landing exactly on the maximum would only tie an opponent on it.
+        let (win, tie) = match self.ruleset.exact_win() && state.queued() == self.max() {
+            true => (tie, T::ZERO),
+            false => (win, tie),
+        };
+        win + win + tie * (T::ONE + self.tie_payoff()) - T::ONE
+            + self.calc_terminal_bust_payoff(state, dice_rolled)
+    }
+    /// Returns the payoff of equal final scores to the final-round mover.
+    fn tie_payoff(&self) -> T {
+        let tie = self.ruleset.tie_payoff();
+        T::from_f64(tie.expect("checked when the solver was created"))
+    }
+    /// Calculate how much better than a loss the busting totals of rolling
+    /// `dice_rolled` dice in a terminal state are (0 if busting loses).
+    fn calc_terminal_bust_payoff(&self, state: State, dice_rolled: u32) -> T {
+        let (active, queued) = (state.active(), state.queued());
+        let headroom = self.max() - active;
+        let bust = T::ONE - self.pmfs.cdf(dice_rolled, headroom);
+        // A win is worth 2 and a tie 1 (with `TieRule::Draw`) more than a loss.
+        let tied = T::ONE + self.tie_payoff();
+        let compare = |score: u32| match score.cmp(&queued) {
+            Ordering::Less => T::ZERO,
+            Ordering::Equal => bust * tied,
+            Ordering::Greater => bust + bust,
+        };
+        match self.ruleset.bust_rule() {
+            BustRule::Lose => T::ZERO,
+            BustRule::Reset => compare(0),
+            BustRule::Revert => compare(active),
+            BustRule::Bounce => {
+                // A total of `t` bounces back to `bounce - t` (at least 0), which
+                // beats the opponent for totals below `bounce - queued`.
+                let bounce = 2 * self.max() - active;
+                let level = bounce - queued;
+                let win = self.pmfs.probability_between(
+                    dice_rolled,
+                    headroom + 1,
+                    level.saturating_sub(1),
+                );
+                let tie = match queued {
+                    0 => T::ONE - self.pmfs.cdf(dice_rolled, bounce - 1),
+                    _ => self
+                        .pmfs
+                        .probability_between(dice_rolled, level.max(headroom + 1), level),
+                };
+                win + win + tie * tied
+            }
+        }
+    }
+    /// Calculate expected payoff for rolling a specific number of dice in a
+    /// terminal state by summing over every dice total.
+    ///
+    /// This is the O(n × sides) counterpart of `calc_terminal_payoff`, kept
+    /// as a reference implementation.
+    pub fn calc_terminal_payoff_direct(&self, state: State, dice_rolled: u32) -> T {
+        if dice_rolled == 0 {
+            return self.calc_terminal_payoff(state, 0);
+        }
+
+        (dice_rolled..=self.sides() * dice_rolled).fold(T::ZERO, |acc, dice_total| {
             let probability = self.pmfs.lookup(dice_rolled, dice_total);
-            match (state.active() + dice_total).cmp(&state.queued()) {
-                Ordering::Greater if state.active() + dice_total <= self.max() => acc + probability, // higher valid score
-                Ordering::Less | Ordering::Greater => acc - probability, // lower score or bust
-                Ordering::Equal => acc,                                  // tie
+            match self.ruleset.score_after(state.active(), dice_total) {
+                _ if self.ruleset.exact_win() && state.active() + dice_total == self.max() => {
+                    acc + probability // exact maximum
+                }
+                Some(score) => match score.cmp(&state.queued()) {
+                    Ordering::Greater => acc + probability, // higher valid score
+                    Ordering::Less => acc - probability,    // lower score
+                    Ordering::Equal => acc + probability * self.tie_payoff(), // tie
+                },
+                None => acc - probability, // bust
             }
         })
     }
 }
 
-impl DpSolver {
+impl<T: Scalar> DpSolver<T> {
     /// Compute optimal actions for all normal (non-terminal) game states.
     ///
     /// Uses dynamic programming with a specific ordering constraint: states
     /// must be processed in decreasing order of (active + queued) score to
     /// ensure all reachable future states have already been computed.
     ///
+    /// # Batched Payoffs
+    ///
+    /// Rather than summing over every dice total for every `n` (as
+    /// `calc_normal_payoff` does), the payoffs of all `n` are computed at once
+    /// by repeatedly convolving the already-solved payoff vector with a single
+    /// die. For an opponent score `q`, let `W⁰(x)` be the mover's payoff for
+    /// landing on `x` (-1 past the maximum) and
+    ///
+    /// `Wⁿ(x) = (1/sides) × Σ_{k=1..=sides} Wⁿ⁻¹(x + k)`
+    ///
+    /// Then rolling `n` dice in the state `(a, q)` has payoff `Wⁿ(a)`. Every
+    /// `Wⁿ(a)` only needs `Wⁿ⁻¹` at the `sides` scores just above `a`, which
+    /// belong to states of higher order, so each state costs O(max_n × sides)
+    /// instead of O(max_n² × sides).
+    ///
     /// # Ordering Requirement
     ///
     /// Normal states reference other normal states and terminal states, so they
@@ -327,13 +986,249 @@ impl DpSolver {
     ///
     /// # Parallelization
     ///
-    /// States within each order can be computed in parallel since they don't
-    /// depend on each other.
+    /// A state only needs a few states of higher order: the previous state of
+    /// its window and the `sides` states whose payoffs it reads. So rather
+    /// than waiting for each order to finish, the orders are grouped into
+    /// tiles and every state of a tile is scheduled as soon as its
+    /// dependencies are solved. The only barriers are between tiles.
+    ///
+    /// # Soft Busts
+    ///
+    /// With a soft `BustRule`, a bust lands on a state that is not of higher
+    /// order. With `BustRule::Revert` it lands on the swapped state of the
+    /// same order, so each order is solved on its own, repeating its sweep
+    /// until no payoff changes by more than `T::TIE_TOLERANCE`. Otherwise
+    /// there is no such ordering, and the normal states are solved by value
+    /// iteration instead: the sweep of `solve_normal_states_direct` is
+    /// repeated over every order (reading the previous sweep's payoffs
+    /// wherever a bust lands) until it converges, for at most
+    /// `SWEEPS_PER_SIDE × sides` sweeps.
+    ///
+    /// # Panics
+    ///
+    /// Panics if soft busts do not converge within that many sweeps.
     pub fn solve_normal_states(&mut self) {
-        // Process each order sequentially (constraint of the dynamic programming).
+        self.solve_normal_states_with(|_| {}, &CancelToken::new())
+            .unwrap_or_else(|error| panic!("{error}"));
+    }
+    /// Compute optimal actions for all normal states (like
+    /// `solve_normal_states`), reporting progress for every order and
+    /// checking `cancel` before every tile of orders.
+    ///
+    /// When resuming, only the orders after the checkpoint are solved. When
+    /// checkpointing, a checkpoint is written periodically and on
+    /// cancellation.
+    ///
+    /// With a soft `BustRule`, progress is reported for every order (of every
+    /// sweep, whose fraction starts over, unless busts revert), cancellation
+    /// is checked before every order, and no checkpoints are written.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolveError::Cancelled` if the token was cancelled before every
+    /// order was solved, `SolveError::Checkpoint` if a checkpoint could not be
+    /// written, or `SolveError::NotConverged` if a soft `BustRule` did not
+    /// converge.
+    pub fn solve_normal_states_with(
+        &mut self,
+        mut progress: impl FnMut(Progress),
+        cancel: &CancelToken,
+    ) -> Result<(), SolveError> {
+        match self.ruleset.bust_rule() {
+            BustRule::Lose => {}
+            BustRule::Revert => return self.solve_by_order(progress, cancel),
+            BustRule::Reset | BustRule::Bounce => {
+                return self.solve_by_iteration(progress, cancel);
+            }
+        }
+        let (first, mut windows) = match self.resume.take() {
+            Some(resume) => resume,
+            None => (2 * self.max(), Windows::new(self.max(), self.sides())),
+        };
+        let start = Instant::now();
+        let states = (u64::from(self.max()) + 1).pow(2);
+        let mut solved: u64 = (first + 1..=2 * self.max())
+            .map(|order| u64::from(order.min(2 * self.max() - order)) + 1)
+            .sum();
+        let mut saved = Instant::now();
+
+        // Process each tile sequentially (constraint of the dynamic programming).
+        let mut hi = first;
+        loop {
+            if cancel.is_cancelled() {
+                if let Some((path, _)) = &self.checkpoints {
+                    checkpoint::write(
+                        path,
+                        self.ruleset,
+                        self.pmf_backend,
+                        hi,
+                        &self.policy,
+                        &windows,
+                    )
+                    .map_err(SolveError::Checkpoint)?;
+                }
+                return Err(SolveError::Cancelled);
+            }
+            let tile = self.next_tile(hi);
+            for (state, action) in wavefront::solve_tile(self, &tile, &mut windows) {
+                self.policy.set(&state, action);
+            }
+
+            for order in (tile.lo()..=tile.hi()).rev() {
+                solved += u64::from(order.min(2 * self.max() - order)) + 1;
+                progress(Progress::new(
+                    Phase::Normal,
+                    Some(order),
+                    2 * self.max(),
+                    (solved, states),
+                    start.elapsed(),
+                ));
+            }
+
+            if tile.lo() == 0 {
+                break;
+            }
+            hi = tile.lo() - 1;
+            if let Some((path, every)) = &self.checkpoints
+                && saved.elapsed() >= *every
+            {
+                checkpoint::write(
+                    path,
+                    self.ruleset,
+                    self.pmf_backend,
+                    hi,
+                    &self.policy,
+                    &windows,
+                )
+                .map_err(SolveError::Checkpoint)?;
+                saved = Instant::now();
+            }
+        }
+        Ok(())
+    }
+    /// Compute optimal actions for all normal states by evaluating every
+    /// dice count of every state independently with `calc_normal_payoff`.
+    ///
+    /// This is the straightforward (and much slower) counterpart of
+    /// `solve_normal_states`, kept as a reference implementation.
+    ///
+    /// With `EndRule::BothStand`, the terminal states of each order are solved
+    /// first (their rolls continue like normal states), since the normal
+    /// states of the same order stand into them. With a soft `BustRule`, each
+    /// order (with `BustRule::Revert`) or the whole sweep is repeated until
+    /// it converges.
+    ///
+    /// # Errors
+    ///
+    /// Returns `SolveError::NotConverged` if a soft `BustRule` did not
+    /// converge within `SWEEPS_PER_SIDE × sides` sweeps.
+    pub fn solve_normal_states_direct(&mut self) -> Result<(), SolveError> {
+        if self.ruleset.bust_rule() == BustRule::Revert {
+            for order in (0..=2 * self.max()).rev() {
+                self.solve_order_to_convergence(order)?;
+            }
+            return Ok(());
+        }
+        for _ in 0..self.max_sweeps() {
+            let mut change: f64 = 0.0;
+            // Process each order sequentially (constraint of the dynamic programming).
+            for order in (0..=2 * self.max()).rev() {
+                change = change.max(self.solve_order_direct(order));
+            }
+            if self.ruleset.bust_rule() == BustRule::Lose || change <= T::TIE_TOLERANCE {
+                return Ok(());
+            }
+        }
+        Err(SolveError::NotConverged(self.max_sweeps()))
+    }
+    /// The most sweeps of value iteration with a soft `BustRule`.
+    fn max_sweeps(&self) -> u32 {
+        SWEEPS_PER_SIDE.saturating_mul(self.sides())
+    }
+    /// Solve the normal states order by order (with `BustRule::Revert`),
+    /// reporting progress for every order.
+    fn solve_by_order(
+        &mut self,
+        mut progress: impl FnMut(Progress),
+        cancel: &CancelToken,
+    ) -> Result<(), SolveError> {
+        let start = Instant::now();
+        let states = (u64::from(self.max()) + 1).pow(2);
+        let mut solved = 0;
         for order in (0..=2 * self.max()).rev() {
+            if cancel.is_cancelled() {
+                return Err(SolveError::Cancelled);
+            }
+            self.solve_order_to_convergence(order)?;
+            solved += u64::from(order.min(2 * self.max() - order)) + 1;
+            progress(Progress::new(
+                Phase::Normal,
+                Some(order),
+                2 * self.max(),
+                (solved, states),
+                start.elapsed(),
+            ));
+        }
+        Ok(())
+    }
+    /// Repeat `solve_order_direct` until no payoff of `order` changes by more
+    /// than `T::TIE_TOLERANCE`, or fail with `SolveError::NotConverged`.
+    ///
+    /// A reverting bust lands on the swapped state of the same order (and
+    /// with `EndRule::BothStand`, a stand on the swapped terminal state), so
+    /// with the higher orders solved, the order only depends on itself.
+    fn solve_order_to_convergence(&mut self, order: u32) -> Result<(), SolveError> {
+        for _ in 0..self.max_sweeps() {
+            if self.solve_order_direct(order) <= T::TIE_TOLERANCE {
+                return Ok(());
+            }
+        }
+        Err(SolveError::NotConverged(self.max_sweeps()))
+    }
+    /// Solve the normal states by value iteration (with a soft `BustRule`),
+    /// reporting progress for every order of every sweep.
+    fn solve_by_iteration(
+        &mut self,
+        mut progress: impl FnMut(Progress),
+        cancel: &CancelToken,
+    ) -> Result<(), SolveError> {
+        let start = Instant::now();
+        let states = (u64::from(self.max()) + 1).pow(2);
+        for _ in 0..self.max_sweeps() {
+            let mut change: f64 = 0.0;
+            let mut solved = 0;
+            for order in (0..=2 * self.max()).rev() {
+                if cancel.is_cancelled() {
+                    return Err(SolveError::Cancelled);
+                }
+                change = change.max(self.solve_order_direct(order));
+                solved += u64::from(order.min(2 * self.max() - order)) + 1;
+                progress(Progress::new(
+                    Phase::Normal,
+                    Some(order),
+                    2 * self.max(),
+                    (solved, states),
+                    start.elapsed(),
+                ));
+            }
+            if change <= T::TIE_TOLERANCE {
+                return Ok(());
+            }
+        }
+        Err(SolveError::NotConverged(self.max_sweeps()))
+    }
+    /// Solve every normal state of `order` (and with `EndRule::BothStand`
+    /// every terminal state, first) with `find_optimal_normal_action`,
+    /// returning the largest change of any payoff.
+    fn solve_order_direct(&mut self, order: u32) -> f64 {
+        let layers: &[bool] = match self.ruleset.end_rule() {
+            EndRule::BothStand => &[true, false],
+            EndRule::LastTurn | EndRule::Immediate => &[false],
+        };
+        let mut change: f64 = 0.0;
+        for &last in layers {
             // For each order, process places in parallel.
-            let states_actions: Vec<(State, Action)> = (0..=order.min(2 * self.max() - order))
+            let states_actions: Vec<(State, Action<T>)> = (0..=order.min(2 * self.max() - order))
                 .into_par_iter() // Parallelize only within each order.
                 .map(|place| {
                     // Calculate the player and opponent score for this order and place.
@@ -342,7 +1237,7 @@ impl DpSolver {
                     } else {
                         (self.max() - place, (order - self.max()) + place)
                     };
-                    let state = State::new(turn, next, false);
+                    let state = State::new(turn, next, last);
                     let action = self.find_optimal_normal_action(state);
                     (state, action)
                 })
@@ -350,9 +1245,50 @@ impl DpSolver {
 
             // Insert the results for this order into the policy.
             for (state, action) in states_actions {
+                let previous = self.policy.get(&state).payoff;
+                change = change.max((action.payoff - previous).abs().to_f64());
                 self.policy.set(&state, action);
             }
         }
+        change
+    }
+    /// The tile of orders starting at `hi` (and going down).
+    fn next_tile(&self, hi: u32) -> Tile {
+        let orders = self
+            .tile_height
+            .unwrap_or_else(|| (2 * self.max() + 1).isqrt().max(MIN_TILE_HEIGHT));
+        let lo = hi.saturating_sub(orders - 1);
+        Tile::new(self.max(), self.sides(), lo, hi)
+    }
+    /// Find the optimal number of dice to roll in a normal state, reading
+    /// (and extending) the batched payoffs in the window of its opponent
+    /// score.
+    pub(super) fn find_optimal_normal_action_batched(
+        &self,
+        state: State,
+        window: &mut Window<T>,
+    ) -> Action<T> {
+        window.extend(state.active(), self.sides());
+        self.choose_action(
+            state,
+            self.calc_normal_payoff(state, 0),
+            window.payoffs(state.active()),
+        )
+    }
+    /// Choose between standing (worth `stand`) and rolling, given the batched
+    /// payoffs `Wⁿ` of rolling `n` dice in `state`.
+    pub(super) fn choose_action(&self, state: State, stand: T, payoffs: &[T]) -> Action<T> {
+        let max_optimal_n = self.max_optimal_n(state);
+        let tolerance = T::from_f64(T::TIE_TOLERANCE);
+        (1..=max_optimal_n).fold(Action::new(0, stand), |optimal, dice_rolled| {
+            let payoff = payoffs[dice_rolled as usize];
+            // If equal (up to rounding), the less aggressive move is taken.
+            if payoff > optimal.payoff + tolerance {
+                Action::new(dice_rolled, payoff)
+            } else {
+                optimal
+            }
+        })
     }
     /// Find the optimal number of dice to roll in a normal (non-terminal)
     /// state.
@@ -365,18 +1301,111 @@ impl DpSolver {
     ///
     /// All reachable future states (both normal and terminal) must already be
     /// solved.
-    pub fn find_optimal_normal_action(&self, state: State) -> Action {
-        // The mean is $(n)(s + 1) / 2$, thus the $n$ for which the mean next score is
-        // greater than the max score is $ceil(2 * (MAX - a) / (s + 1))$. This is the
-        // same as $2 * (MAX - a + s) / (s + 1)$. This is how `max_optimal_n` is
-        // calculated.
-        let max_optimal_n = 2 * (self.max() - state.active() + self.sides()) / (self.sides() + 1);
-        let (optimal_roll, optimal_payoff) = (0..=max_optimal_n)
+    pub fn find_optimal_normal_action(&self, state: State) -> Action<T> {
+        let (optimal_roll, optimal_payoff) = (0..=self.max_optimal_n(state))
             .rev() // If equal, the less aggressive move is taken.
             .map(|dice_rolled| (dice_rolled, self.calc_normal_payoff(state, dice_rolled)))
             .max_by(|a, b| a.1.partial_cmp(&b.1).unwrap())
             .unwrap();
-        Action::new(optimal_roll, optimal_payoff)
+        let optimal = Action::new(optimal_roll, optimal_payoff);
+        match self.ruleset.bust_rule() {
+            BustRule::Bounce => self.find_optimal_bounce_action(state, optimal),
+            BustRule::Lose | BustRule::Reset | BustRule::Revert => optimal,
+        }
+    }
+    /// Search the dice counts past the first certain bust (with
+    /// `BustRule::Bounce`) for one that beats `optimal`.
+    ///
+    /// Rolling one more die moves the landing score of a certain bust down by
+    /// one more die. So if `ceiling(L)` is the best payoff of landing on any
+    /// score up to `L`, which never decreases, the expected `ceiling` of the
+    /// landing score of `n` dice bounds the payoff of every count from `n` up.
+    /// The search stops as soon as that bound is no better than `optimal`,
+    /// which is right after the first certain bust whenever a higher landing
+    /// score is never worse.
+    fn find_optimal_bounce_action(&self, state: State, mut optimal: Action<T>) -> Action<T> {
+        let headroom = self.max() - state.active();
+        // Every count past `max + headroom` bounces all the way back to 0.
+        let last = self
+            .ruleset
+            .max_dice()
+            .map_or(self.max() + headroom, |max_dice| {
+                max_dice.min(self.max() + headroom)
+            });
+        let mut dice_rolled = headroom + 1;
+        if dice_rolled > last {
+            return optimal;
+        }
+        // A certain bust lands below the maximum.
+        let payoffs: Vec<T> = (0..self.max())
+            .map(|score| {
+                -self
+                    .policy
+                    .get(&State::new(state.queued(), score, false))
+                    .payoff
+            })
+            .collect();
+        let mut ceiling = payoffs.clone();
+        for score in 1..ceiling.len() {
+            if ceiling[score - 1] > ceiling[score] {
+                ceiling[score] = ceiling[score - 1];
+            }
+        }
+        // The probability of landing on each score after rolling `dice_rolled`.
+        let floor = self.max() + headroom;
+        let mut landings = vec![T::ZERO; self.max() as usize];
+        landings[0] = T::ONE - self.pmfs.cdf(dice_rolled, floor - 1);
+        for dice_total in dice_rolled..=(self.sides() * dice_rolled).min(floor - 1) {
+            landings[(floor - dice_total) as usize] += self.pmfs.lookup(dice_rolled, dice_total);
+        }
+        let expect = |values: &[T], landings: &[T]| -> T {
+            values
+                .iter()
+                .zip(landings)
+                .map(|(&value, &probability)| value * probability)
+                .sum()
+        };
+        let face = T::ONE / T::from_f64(f64::from(self.sides()));
+        // If equal (up to rounding), the less aggressive move is taken.
+        let tolerance = T::from_f64(T::TIE_TOLERANCE);
+        while expect(&ceiling, &landings) > optimal.payoff + tolerance && dice_rolled < last {
+            // Roll one more die.
+            let mut next = vec![T::ZERO; landings.len()];
+            for (score, &probability) in landings.iter().enumerate() {
+                for roll in 1..=self.sides() as usize {
+                    next[score.saturating_sub(roll)] += probability * face;
+                }
+            }
+            landings = next;
+            dice_rolled += 1;
+            let payoff = expect(&payoffs, &landings);
+            if payoff > optimal.payoff + tolerance {
+                optimal = Action::new(dice_rolled, payoff);
+            }
+        }
+        optimal
+    }
+    /// The largest number of dice worth considering (and allowed) in `state`.
+    ///
+    /// A soft bust is not a loss, so the bound for `BustRule::Lose` does not
+    /// hold. Instead, every dice count is tried up to the first one that is
+    /// certain to bust. With `BustRule::Reset` and `BustRule::Revert`, every
+    /// larger count lands on the same state, so nothing is lost. With
+    /// `BustRule::Bounce`, larger counts bounce further back: on the last turn
+    /// a lower final score is never better, and otherwise
+    /// `find_optimal_bounce_action` searches them until none can do better.
+    fn max_optimal_n(&self, state: State) -> u32 {
+        let headroom = self.max() - state.active();
+        let max_optimal_n = match self.ruleset.bust_rule() {
+            // The mean is $(n)(s + 1) / 2$, thus the $n$ for which the mean next score is
+            // greater than the max score is $ceil(2 * (MAX - a) / (s + 1))$. This is the
+            // same as $2 * (MAX - a + s) / (s + 1)$.
+            BustRule::Lose => 2 * (headroom + self.sides()) / (self.sides() + 1),
+            BustRule::Reset | BustRule::Revert | BustRule::Bounce => headroom + 1,
+        };
+        self.ruleset
+            .max_dice()
+            .map_or(max_optimal_n, |max_dice| max_optimal_n.min(max_dice))
     }
     /// Calculate expected payoff for rolling a specific number of dice in a
     /// normal state.
@@ -386,30 +1415,69 @@ impl DpSolver {
     /// Rolling 0 dice triggers the terminal round with swapped player
     /// positions.
     ///
+    /// With `EndRule::BothStand`, this is also the payoff of a terminal state,
+    /// where rolling continues normal play and rolling 0 dice ends the game.
+    ///
+    /// With `Ruleset::with_exact_win`, landing exactly on the maximum is worth
+    /// a win instead of the payoff of the opponent's next move.
+    ///
     /// # Prerequisites
     ///
     /// All reachable future states must already be solved for correct payoff
     /// lookup.
     #[must_use]
-    pub fn calc_normal_payoff(&self, state: State, dice_rolled: u32) -> f64 {
+    pub fn calc_normal_payoff(&self, state: State, dice_rolled: u32) -> T {
+        if dice_rolled == 0 && state.last() {
+            return self.calc_terminal_payoff(state, 0);
+        }
         if dice_rolled == 0 {
             let terminal_state = State::new(state.queued(), state.active(), true);
             return -self.policy.get(&terminal_state).payoff;
         }
-        (dice_rolled..=self.sides() * dice_rolled).fold(0.0, |acc, dice_total| {
-            let probability: f64 = self.pmfs.lookup(dice_rolled, dice_total);
-            let payoff = if state.active() + dice_total <= self.max() {
-                let state = State::new(state.queued(), state.active() + dice_total, false);
-                -self.policy.get(&state).payoff
-            } else {
-                -1.0
-            };
-            acc + probability * payoff
+        let headroom = self.max() - state.active();
+        let bust = self.calc_normal_bust_payoff(state, dice_rolled);
+        (dice_rolled..=(self.sides() * dice_rolled).min(headroom)).fold(bust, |acc, dice_total| {
+            let probability = self.pmfs.lookup(dice_rolled, dice_total);
+            if self.ruleset.exact_win() && dice_total == headroom {
+                return acc + probability;
+            }
+            let state = State::new(state.queued(), state.active() + dice_total, false);
+            acc - probability * self.policy.get(&state).payoff
         })
     }
+    /// Calculate the part of the expected payoff of rolling `dice_rolled`
+    /// dice in a normal state that comes from busting totals.
+    fn calc_normal_bust_payoff(&self, state: State, dice_rolled: u32) -> T {
+        let headroom = self.max() - state.active();
+        let bust = T::ONE - self.pmfs.cdf(dice_rolled, headroom);
+        // After a soft bust, the opponent moves from wherever it landed.
+        let landing = |total: u32| {
+            let score = self.ruleset.score_after(state.active(), total);
+            let score = score.expect("a soft bust always lands on a score");
+            -self
+                .policy
+                .get(&State::new(state.queued(), score, false))
+                .payoff
+        };
+        match self.ruleset.bust_rule() {
+            BustRule::Lose => -bust,
+            // Every busting total lands on the same score.
+            BustRule::Reset | BustRule::Revert => bust * landing(headroom + 1),
+            // Every total that bounces past 0 lands on 0.
+            BustRule::Bounce => {
+                let floor = self.max() + headroom;
+                let past = T::ONE - self.pmfs.cdf(dice_rolled, floor - 1);
+                let bounced =
+                    (headroom + 1).max(dice_rolled)..=(self.sides() * dice_rolled).min(floor - 1);
+                bounced.fold(past * landing(floor), |acc, dice_total| {
+                    acc + self.pmfs.lookup(dice_rolled, dice_total) * landing(dice_total)
+                })
+            }
+        }
+    }
 }
 
-impl DpSolver {
+impl<T: Scalar> DpSolver<T> {
     /// Output the complete policy in human-readable format to stdout.
     pub fn stdout(&self) {
         let mut state_action_pairs: Vec<_> = self.policy.clone().iter().collect();
@@ -459,7 +1527,7 @@ impl DpSolver {
                 state.queued(),
                 state.last(),
                 action.n,
-                action.payoff,
+                action.payoff.to_f64(),
             ))?;
         }
         writer.flush()?;
@@ -489,7 +1557,7 @@ impl DpSolver {
                 state.queued(),
                 state.last(),
                 action.n,
-                action.payoff,
+                action.payoff.to_f64(),
             ))?;
         }
         writer.flush()?;
@@ -516,20 +1584,22 @@ impl DpSolver {
     }
 }
 
-impl Solver for DpSolver {
+impl<T: Scalar> Solver<T> for DpSolver<T> {
     /// Returns the ruleset used by the solver.
     fn ruleset(&self) -> Ruleset {
-        self.ruleset.clone()
+        self.ruleset
     }
     /// Returns the policy computed by the solver.
-    fn policy(&mut self) -> Policy {
+    fn policy(&mut self) -> Policy<T> {
         self.solve();
         self.policy.clone()
     }
 }
 
+#[cfg(test)]
 mod tests {
     use super::*;
+    use crate::{DoubleDouble, TieRule};
 
     #[test]
     fn test_solver_vs_known_optimal_strategies() {
@@ -548,6 +1618,455 @@ mod tests {
         assert!(action.n > 0, "Must roll when behind in terminal state");
     }
 
+    #[test]
+    fn test_batched_normal_states_match_direct() {
+        let mut batched = DpSolver::new(40, 5);
+        batched.solve();
+        let mut direct = batched.clone();
+        direct.solve_normal_states_direct().unwrap();
+
+        for (state, action) in batched.policy.iter() {
+            let expected = direct.policy.get(&state);
+            assert!(
+                (action.payoff - expected.payoff).abs() < 1e-10,
+                "{state:?}: {} != {}",
+                action.payoff,
+                expected.payoff
+            );
+        }
+    }
+
+    #[test]
+    fn test_end_rules_match_direct() {
+        for end_rule in [EndRule::Immediate, EndRule::BothStand] {
+            let ruleset = Ruleset::new(30, 5).with_end_rule(end_rule);
+            let mut batched = DpSolver::from_ruleset(ruleset).with_tile_height(7);
+            batched.solve();
+            let mut direct = DpSolver::from_ruleset(ruleset);
+            direct.precompute_pmfs();
+            direct.solve_terminal_states();
+            direct.solve_normal_states_direct().unwrap();
+
+            for (state, action) in batched.policy.iter() {
+                let expected = direct.policy.get(&state);
+                assert!(
+                    (action.payoff - expected.payoff).abs() < 1e-10,
+                    "{end_rule} {state:?}: {} != {}",
+                    action.payoff,
+                    expected.payoff
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_end_rules() {
+        let solve =
+            |end_rule| DpSolver::from_ruleset(Ruleset::new(40, 6).with_end_rule(end_rule)).policy();
+        let (last_turn, immediate, both_stand) = (
+            solve(EndRule::LastTurn),
+            solve(EndRule::Immediate),
+            solve(EndRule::BothStand),
+        );
+
+        // Standing ends an immediate game, so it is worth comparing scores.
+        let payoff = immediate.get(&State::new(20, 25, true)).payoff();
+        assert_eq!(payoff, -1.0);
+        let stand = State::new(40, 10, false);
+        assert_eq!(immediate.get(&stand).n(), 0);
+        assert_eq!(immediate.get(&stand).payoff(), 1.0);
+
+        // When both must stand, trailing after a stand is never final.
+        let behind = State::new(10, 30, true);
+        assert!(both_stand.get(&behind).n() > 0);
+        assert!(both_stand.get(&behind).payoff() > -1.0);
+        // Leading after a stand, standing ends the game with a win.
+        assert_eq!(both_stand.get(&State::new(30, 10, true)).payoff(), 1.0);
+
+        for policy in [&immediate, &both_stand] {
+            let differs = last_turn
+                .iter()
+                .zip(policy.iter())
+                .any(|((_, a), (_, b))| a.n() != b.n());
+            assert!(differs);
+        }
+    }
+
+    #[test]
+    fn test_tile_heights_agree() {
+        let expected = DpSolver::new(40, 6).policy();
+        for height in [1, 3, 81] {
+            let policy = DpSolver::new(40, 6).with_tile_height(height).policy();
+            for ((state, a), (_, b)) in policy.iter().zip(expected.iter()) {
+                assert_eq!((a.n(), a.payoff()), (b.n(), b.payoff()), "{state:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_terminal_payoff_matches_direct() {
+        let rules = BustRule::ALL.into_iter().flat_map(|rule| {
+            [(rule, false), (rule, true)]
+                .into_iter()
+                .flat_map(|(rule, exact)| {
+                    [TieRule::Draw, TieRule::Stander].map(|t| (rule, exact, t))
+                })
+        });
+        for (bust_rule, exact_win, tie_rule) in rules {
+            let ruleset = Ruleset::new(30, 4)
+                .with_bust_rule(bust_rule)
+                .with_exact_win(exact_win)
+                .with_tie_rule(tie_rule);
+            let mut solver = DpSolver::from_ruleset(ruleset);
+            solver.precompute_pmfs();
+
+            for active in 0..=30 {
+                for queued in 0..=30 {
+                    let state = State::new(active, queued, true);
+                    for dice_rolled in 0..=12 {
+                        let fast = solver.calc_terminal_payoff(state, dice_rolled);
+                        let direct = solver.calc_terminal_payoff_direct(state, dice_rolled);
+                        assert!(
+                            (fast - direct).abs() < 1e-12,
+                            "{ruleset:?} {state:?}, n={dice_rolled}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_win() {
+        for end_rule in EndRule::ALL {
+            let ruleset = Ruleset::new(30, 6)
+                .with_end_rule(end_rule)
+                .with_exact_win(true);
+            let mut batched = DpSolver::from_ruleset(ruleset).with_tile_height(5);
+            batched.solve();
+            let mut direct = DpSolver::from_ruleset(ruleset);
+            direct.precompute_pmfs();
+            direct.solve_terminal_states();
+            direct.solve_normal_states_direct().unwrap();
+
+            for (state, action) in batched.policy.iter() {
+                let expected = direct.policy.get(&state);
+                assert!(
+                    (action.payoff - expected.payoff).abs() < 1e-10,
+                    "{end_rule} {state:?}: {} != {}",
+                    action.payoff,
+                    expected.payoff
+                );
+            }
+        }
+
+        // On the last turn, a 1 beats an opponent on the maximum instead of
+        // tying them, and anything else busts.
+        let policy = DpSolver::from_ruleset(Ruleset::new(30, 6).with_exact_win(true)).policy();
+        let action = policy.get(&State::new(29, 30, true));
+        assert_eq!(action.n(), 1);
+        assert!((action.payoff() - (1.0 - 5.0) / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    fn test_tie_rules() {
+        let draw = DpSolver::new(30, 6).policy();
+        for end_rule in EndRule::ALL {
+            let ruleset = Ruleset::new(30, 6)
+                .with_end_rule(end_rule)
+                .with_tie_rule(TieRule::Stander);
+            let mut batched = DpSolver::from_ruleset(ruleset).with_tile_height(5);
+            batched.solve();
+            let mut direct = DpSolver::from_ruleset(ruleset);
+            direct.precompute_pmfs();
+            direct.solve_terminal_states();
+            direct.solve_normal_states_direct().unwrap();
+
+            for (state, action) in batched.policy.iter() {
+                let expected = direct.policy.get(&state);
+                assert!(
+                    (action.payoff - expected.payoff).abs() < 1e-10,
+                    "{end_rule} {state:?}: {} != {}",
+                    action.payoff,
+                    expected.payoff
+                );
+            }
+        }
+
+        // A roll-off between equal dice is a coin flip, worth a draw.
+        let sudden_death = Ruleset::new(30, 6).with_tie_rule(TieRule::SuddenDeath);
+        let policy = DpSolver::from_ruleset(sudden_death).policy();
+        for ((state, a), (_, b)) in policy.iter().zip(draw.iter()) {
+            assert_eq!((a.n(), a.payoff()), (b.n(), b.payoff()), "{state:?}");
+        }
+
+        // Level on 29 with a player who stood, standing would lose, so the
+        // last turn rolls a die, hoping for a 1.
+        let stander = Ruleset::new(30, 6).with_tie_rule(TieRule::Stander);
+        let policy = DpSolver::from_ruleset(stander).policy();
+        let state = State::new(29, 29, true);
+        assert_eq!(draw.get(&state).n(), 0);
+        assert_eq!(policy.get(&state).n(), 1);
+        assert!((policy.get(&state).payoff() - (1.0 - 5.0) / 6.0).abs() < 1e-12);
+    }
+
+    #[test]
+    #[should_panic(expected = "depends on the seat")]
+    fn test_second_player_ties_need_seats() {
+        let ruleset = Ruleset::new(30, 6).with_tie_rule(TieRule::SecondPlayer);
+        assert!(DpSolver::try_from_ruleset(ruleset).is_err());
+        let _ = DpSolver::from_ruleset(ruleset);
+    }
+
+    #[test]
+    fn test_max_dice() {
+        let ruleset = Ruleset::new(40, 6).with_max_dice(4);
+        let mut batched = DpSolver::from_ruleset(ruleset).with_tile_height(6);
+        batched.solve();
+        let mut direct = DpSolver::from_ruleset(ruleset);
+        direct.precompute_pmfs();
+        direct.solve_terminal_states();
+        direct.solve_normal_states_direct().unwrap();
+
+        for (state, action) in batched.policy.iter() {
+            assert!(action.n() <= 4, "{state:?}");
+            let expected = direct.policy.get(&state);
+            assert!((action.payoff - expected.payoff).abs() < 1e-10, "{state:?}");
+        }
+        // The capped terminal search finds the best allowed dice count.
+        for (state, action) in batched.policy.iter().filter(|(s, _)| s.last()) {
+            let best = (0..=4)
+                .map(|n| batched.calc_terminal_payoff(state, n))
+                .fold(f64::NEG_INFINITY, f64::max);
+            assert!((action.payoff - best).abs() < 1e-12, "{state:?}");
+        }
+
+        // From 0 against 40, no 4 dice can catch up.
+        let action = batched.policy.get(&State::new(0, 40, true));
+        assert_eq!((action.n(), action.payoff()), (0, -1.0));
+
+        assert!(ruleset.validate_dice(4).is_ok());
+        let error = ruleset.validate_dice(5).unwrap_err();
+        assert_eq!((error.n, error.max_dice), (5, 4));
+        assert!(Ruleset::new(40, 6).try_with_max_dice(0).is_err());
+    }
+
+    #[test]
+    fn test_soft_busts_reach_a_fixed_point() {
+        for bust_rule in [BustRule::Reset, BustRule::Revert, BustRule::Bounce] {
+            for end_rule in EndRule::ALL {
+                let ruleset = Ruleset::new(20, 4)
+                    .with_end_rule(end_rule)
+                    .with_bust_rule(bust_rule);
+                let mut solver = DpSolver::from_ruleset(ruleset);
+                solver.solve();
+
+                // One more sweep changes nothing.
+                for (state, action) in solver.policy.iter().filter(|(s, _)| !s.last()) {
+                    let again = solver.find_optimal_normal_action(state);
+                    assert!(
+                        (again.payoff - action.payoff).abs() < 1e-10,
+                        "{bust_rule} {end_rule} {state:?}"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_slowest_soft_busts_converge_within_the_sweep_cap() {
+        // Near the maximum, a reverting roll of many-sided dice stays on the
+        // board with probability 1/20, so each order needs the most sweeps.
+        let ruleset = Ruleset::new(30, 20).with_bust_rule(BustRule::Revert);
+        let result = DpSolver::from_ruleset(ruleset)
+            .with_precision::<DoubleDouble>()
+            .solve_with(|_| {}, &CancelToken::new());
+        assert!(result.is_ok());
+    }
+
+    #[test]
+    fn test_soft_bust_dice_bound() {
+        for bust_rule in [BustRule::Reset, BustRule::Revert, BustRule::Bounce] {
+            for end_rule in EndRule::ALL {
+                for sides in [2, 6] {
+                    let ruleset = Ruleset::new(20, sides)
+                        .with_end_rule(end_rule)
+                        .with_bust_rule(bust_rule);
+                    let mut solver = DpSolver::from_ruleset(ruleset);
+                    solver.solve();
+
+                    // No count past the first certain bust does any better, up
+                    // to the counts that bounce all the way back to 0.
+                    solver.pmfs = PMFLookup::precompute(40, sides);
+                    for (state, action) in solver.policy.iter().filter(|(s, _)| !s.last()) {
+                        for n in solver.max_optimal_n(state) + 1..=41 {
+                            let payoff = solver.calc_normal_payoff(state, n);
+                            assert!(
+                                payoff <= action.payoff + 1e-10,
+                                "{bust_rule} {end_rule} {sides} {state:?} {n}"
+                            );
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_soft_busts_cannot_be_checkpointed() {
+        let dir = tempfile::tempdir().unwrap();
+        let ruleset = Ruleset::new(20, 6).with_bust_rule(BustRule::Revert);
+        let result = DpSolver::from_ruleset(ruleset)
+            .with_checkpoints(dir.path().join("greed.ckpt"), Duration::from_secs(3600))
+            .solve_with(|_| {}, &CancelToken::new());
+        assert!(matches!(result, Err(SolveError::Checkpoint(_))));
+    }
+
+    #[test]
+    fn test_soft_busts() {
+        let solve = |bust_rule| {
+            DpSolver::from_ruleset(Ruleset::new(30, 6).with_bust_rule(bust_rule)).policy()
+        };
+        let lose = solve(BustRule::Lose);
+
+        for bust_rule in [BustRule::Reset, BustRule::Revert, BustRule::Bounce] {
+            let policy = solve(bust_rule);
+            // On the last turn a soft bust is never worse than losing. (Before
+            // that, it spares the opponent's busts too.)
+            let last_turns = policy
+                .iter()
+                .zip(lose.iter())
+                .filter(|((s, _), _)| s.last());
+            for ((state, soft), (_, hard)) in last_turns {
+                assert!(
+                    soft.payoff() >= hard.payoff() - 1e-10,
+                    "{bust_rule} {state:?}"
+                );
+            }
+        }
+
+        // Trailing near the maximum on the last turn, overshooting can still
+        // bounce back past the opponent.
+        let bounce = solve(BustRule::Bounce);
+        let behind = State::new(26, 28, true);
+        assert!(bounce.get(&behind).payoff() > lose.get(&behind).payoff());
+    }
+
+    #[test]
+    fn test_progress_and_cancellation() {
+        let mut reports = Vec::new();
+        let mut solver = DpSolver::new(20, 6);
+        solver
+            .solve_with(|progress| reports.push(progress), &CancelToken::new())
+            .unwrap();
+
+        let phases: Vec<Phase> = reports.iter().map(Progress::phase).collect();
+        assert_eq!(&phases[..2], [Phase::Pmfs, Phase::Terminal]);
+        assert_eq!(reports.len(), 2 + 41, "one report per order");
+        let last = reports.last().unwrap();
+        assert_eq!((last.order(), last.orders()), (Some(0), 40));
+        assert_eq!(last.fraction(), 1.0);
+
+        // Cancel after the first order, which stops after its tile.
+        let cancel = CancelToken::new();
+        let mut orders = 0;
+        let result = DpSolver::new(20, 6).with_tile_height(4).solve_with(
+            |progress| {
+                if progress.phase() == Phase::Normal {
+                    orders += 1;
+                    cancel.cancel();
+                }
+            },
+            &cancel,
+        );
+        assert!(matches!(result, Err(SolveError::Cancelled)));
+        assert_eq!(orders, 4);
+    }
+
+    #[test]
+    fn test_default_tiles_report_in_steps() {
+        // The first tile of a mid-size solve holds √201 = 14 of its orders.
+        let cancel = CancelToken::new();
+        let mut orders = 0;
+        let result = DpSolver::new(100, 6).solve_with(
+            |progress| {
+                if progress.phase() == Phase::Normal {
+                    orders += 1;
+                    cancel.cancel();
+                }
+            },
+            &cancel,
+        );
+        assert!(matches!(result, Err(SolveError::Cancelled)));
+        assert_eq!(orders, 14);
+    }
+
+    #[test]
+    fn test_resume_from_checkpoint() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("greed.ckpt");
+        let expected = DpSolver::new(30, 6).policy();
+
+        // Cancel partway through, writing a checkpoint on the way out.
+        let cancel = CancelToken::new();
+        let mut orders = 0;
+        let result = DpSolver::new(30, 6)
+            .with_tile_height(4)
+            .with_checkpoints(&path, Duration::from_secs(3600))
+            .solve_with(
+                |progress| {
+                    if progress.phase() == Phase::Normal {
+                        orders += 1;
+                        if orders == 25 {
+                            cancel.cancel();
+                        }
+                    }
+                },
+                &cancel,
+            );
+        assert!(matches!(result, Err(SolveError::Cancelled)));
+
+        let mut reports = Vec::new();
+        let mut solver = DpSolver::new(30, 6).resume_from(&path).unwrap();
+        solver
+            .solve_with(|progress| reports.push(progress), &CancelToken::new())
+            .unwrap();
+        // The tile of the 25th order finishes before cancelling.
+        assert_eq!(reports.len(), 2 + 61 - 28, "only the remaining orders");
+        let resumed = solver.into_policy();
+        for ((state, a), (_, b)) in resumed.iter().zip(expected.iter()) {
+            assert_eq!((a.n(), a.payoff()), (b.n(), b.payoff()), "{state:?}");
+        }
+
+        // Checkpoints are tied to their ruleset, precision and PMF backend.
+        assert!(DpSolver::new(30, 4).resume_from(&path).is_err());
+        let stander = Ruleset::new(30, 6).with_tie_rule(TieRule::Stander);
+        assert!(DpSolver::from_ruleset(stander).resume_from(&path).is_err());
+        assert!(
+            DpSolver::new(30, 6)
+                .with_precision::<f32>()
+                .resume_from(&path)
+                .is_err()
+        );
+        assert!(
+            DpSolver::new(30, 6)
+                .with_pmf_backend(PmfBackend::Exact)
+                .resume_from(&path)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_cdf_ranges() {
+        let pmfs: PMFLookup = PMFLookup::precompute(20, 6);
+
+        assert_eq!(pmfs.cdf(3, 2), 0.0);
+        assert_eq!(pmfs.cdf(3, 18), 1.0);
+        assert!((pmfs.probability_between(2, 7, 7) - 6.0 / 36.0).abs() < 1e-12);
+        assert!((pmfs.probability_between(2, 0, 100) - 1.0).abs() < 1e-12);
+        assert_eq!(pmfs.probability_between(2, 8, 7), 0.0);
+    }
+
     #[test]
     fn test_game_symmetry() {
         // Test that the game exhibits expected symmetry properties
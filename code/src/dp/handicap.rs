@@ -0,0 +1,436 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+
+use super::dp::PMFLookup;
+use crate::{
+    Action, BustRule, EndRule, Handicap, HandicapPolicy, Scalar, Seat, State, UnsupportedRuleset,
+};
+
+/// Solves a game where each seat plays by its own ruleset (see `Handicap`).
+///
+/// With even seats, a state looks the same to both players, so `DpSolver`
+/// solves one table from the perspective of whoever moves. Once the seats
+/// differ, the seat to move is part of the state, and there is a table per
+/// seat: in `(a, q)` the mover's score `a` is bounded by its own maximum and
+/// `q` by the opponent's.
+///
+/// The seat also matters when the second player wins ties (see `TieRule`),
+/// so such games are solved here too, even with a single ruleset.
+///
+/// Every roll still increases the combined score, so the states are solved
+/// by decreasing order (`a + q`), both seats together, with every dice total
+/// summed directly.
+///
+/// # Example
+///
+/// ```rust
+/// use greed::{Handicap, HandicapSolver, Ruleset, Seat, State};
+///
+/// // The first player busts past 90 instead of 100.
+/// let handicap = Handicap::new(Ruleset::new(90, 6), Ruleset::new(100, 6));
+/// let policy = HandicapSolver::new(handicap).policy();
+/// let opening = policy.get(&State::new(0, 0, false).with_seat(Seat::First));
+/// println!("Opening: roll {} dice (payoff {:.3})", opening.n(), opening.payoff());
+/// ```
+#[derive(Debug, Clone)]
+pub struct HandicapSolver<T: Scalar = f64> {
+    /// The ruleset of each seat.
+    handicap: Handicap,
+    /// Computed optimal policy of each seat.
+    policy: HandicapPolicy<T>,
+    /// Precomputed probability mass functions for each seat's dice.
+    pmfs: [PMFLookup<T>; 2],
+    /// The payoff of a tie to the first seat, overriding the tie rule.
+    tie: Option<f64>,
+}
+
+impl HandicapSolver {
+    /// Create a new solver for a handicapped game.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the game has soft busts (see `BustRule`), which are only
+    /// solved for even seats (see `try_new`).
+    #[must_use]
+    pub fn new(handicap: impl Into<Handicap>) -> Self {
+        Self::try_new(handicap).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Create a new solver for a handicapped game, if it busts by losing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the game has soft busts (see `BustRule`), which
+    /// are only solved for even seats (by `DpSolver`).
+    pub fn try_new(handicap: impl Into<Handicap>) -> Result<Self, UnsupportedRuleset> {
+        let handicap = handicap.into();
+        match handicap.seat(Seat::First).bust_rule() {
+            BustRule::Lose => Ok(Self::with_handicap(handicap)),
+            BustRule::Reset | BustRule::Revert | BustRule::Bounce => Err(UnsupportedRuleset {
+                reason: "handicapped games must use BustRule::Lose",
+            }),
+        }
+    }
+}
+
+impl<T: Scalar> HandicapSolver<T> {
+    /// Create a new solver (computing in `T`) for a handicapped game that
+    /// busts by losing.
+    fn with_handicap(handicap: Handicap) -> Self {
+        let max = Seat::ALL.map(|seat| handicap.seat(seat).max());
+        HandicapSolver {
+            handicap,
+            policy: HandicapPolicy::new(max[0].max(max[1])),
+            pmfs: [PMFLookup::default(), PMFLookup::default()],
+            tie: None,
+        }
+    }
+    /// Compute payoffs and probabilities in `U` instead.
+    #[must_use]
+    pub fn with_precision<U: Scalar>(self) -> HandicapSolver<U> {
+        HandicapSolver {
+            tie: self.tie,
+            ..HandicapSolver::with_handicap(self.handicap)
+        }
+    }
+    /// Value a tie at `tie` to the first seat (and `-tie` to the second)
+    /// instead of by the tie rule, e.g. to value a drawn game by what it
+    /// leaves of a match.
+    #[must_use]
+    pub fn with_tie_payoff(mut self, tie: f64) -> Self {
+        self.tie = Some(tie);
+        self
+    }
+    /// Returns the ruleset of each seat.
+    #[must_use]
+    pub fn handicap(&self) -> Handicap {
+        self.handicap
+    }
+    /// Solve the game, and return the policy of both seats.
+    pub fn policy(&mut self) -> HandicapPolicy<T> {
+        self.solve();
+        self.policy.clone()
+    }
+    /// Consume the solver, returning its policy (as solved so far).
+    #[must_use]
+    pub fn into_policy(self) -> HandicapPolicy<T> {
+        self.policy
+    }
+    /// Compute the complete optimal policy of both seats.
+    ///
+    /// Terminal states come first (they only compare scores), then the normal
+    /// states by decreasing order. With `EndRule::BothStand`, a terminal
+    /// state rolls back into normal play, so the terminal states of each
+    /// order are solved just before its normal states instead, which stand
+    /// into them.
+    ///
+    /// # Panics
+    ///
+    /// Panics (in release builds too) if any PMF's estimated error exceeds
+    /// `T::PMF_TOLERANCE`.
+    pub fn solve(&mut self) {
+        self.pmfs = Seat::ALL.map(|seat| {
+            let ruleset = self.handicap.seat(seat);
+            PMFLookup::precompute(ruleset.max(), ruleset.sides())
+        });
+
+        let end_rule = self.handicap.seat(Seat::First).end_rule();
+        if end_rule != EndRule::BothStand {
+            let solver = &*self;
+            let actions: Vec<(State, Action<T>)> = Seat::ALL
+                .into_par_iter()
+                .flat_map_iter(|seat| solver.states(seat, true))
+                .map(|state| match end_rule {
+                    EndRule::LastTurn => (state, solver.find_optimal_terminal_action(state)),
+                    _ => (state, Action::new(0, solver.compare(state))),
+                })
+                .collect();
+            for (state, action) in actions {
+                self.policy.set(&state, action);
+            }
+        }
+
+        let layers: &[bool] = match end_rule {
+            EndRule::BothStand => &[true, false],
+            EndRule::LastTurn | EndRule::Immediate => &[false],
+        };
+        let orders = self.max(Seat::First) + self.max(Seat::Second);
+        for order in (0..=orders).rev() {
+            for &last in layers {
+                let solver = &*self;
+                let actions: Vec<(State, Action<T>)> = Seat::ALL
+                    .into_par_iter()
+                    .flat_map_iter(|seat| solver.states_of_order(seat, order, last))
+                    .map(|state| (state, solver.find_optimal_normal_action(state)))
+                    .collect();
+                for (state, action) in actions {
+                    self.policy.set(&state, action);
+                }
+            }
+        }
+    }
+    /// Returns the maximum score of `seat`.
+    fn max(&self, seat: Seat) -> u32 {
+        self.handicap.seat(seat).max()
+    }
+    /// The payoff of ending the game in `state`: comparing the scores (see
+    /// `TieRule`, and `with_tie_payoff`).
+    fn compare(&self, state: State) -> T {
+        match state.active().cmp(&state.queued()) {
+            Ordering::Less => -T::ONE,
+            Ordering::Equal => T::from_f64(match (self.tie, state.seat()) {
+                (Some(tie), Seat::First) => tie,
+                (Some(tie), Seat::Second) => -tie,
+                (None, seat) => self.handicap.tie_payoff(seat),
+            }),
+            Ordering::Greater => T::ONE,
+        }
+    }
+    /// Iterate over every state with `seat` to move.
+    fn states(&self, seat: Seat, last: bool) -> impl Iterator<Item = State> {
+        let (max, other) = (self.max(seat), self.max(seat.other()));
+        (0..=max).flat_map(move |active| {
+            (0..=other).map(move |queued| State::new(active, queued, last).with_seat(seat))
+        })
+    }
+    /// Iterate over the states of `order` with `seat` to move.
+    fn states_of_order(&self, seat: Seat, order: u32, last: bool) -> impl Iterator<Item = State> {
+        let lowest = order.saturating_sub(self.max(seat.other()));
+        let highest = order.min(self.max(seat));
+        (lowest..=highest)
+            .map(move |active| State::new(active, order - active, last).with_seat(seat))
+    }
+    /// The largest number of dice worth considering (and allowed) in `state`,
+    /// as for `DpSolver`.
+    fn max_optimal_n(&self, state: State) -> u32 {
+        let ruleset = self.handicap.seat(state.seat());
+        let (max, sides) = (ruleset.max(), ruleset.sides());
+        let max_optimal_n = 2 * (max - state.active() + sides) / (sides + 1);
+        ruleset
+            .max_dice()
+            .map_or(max_optimal_n, |max_dice| max_optimal_n.min(max_dice))
+    }
+    /// Find the number of dice (fewest among equals) with the best payoff
+    /// `payoff(n)` in `state`.
+    fn optimal_action(&self, state: State, payoff: impl Fn(u32) -> T) -> Action<T> {
+        let tolerance = T::from_f64(T::TIE_TOLERANCE);
+        (1..=self.max_optimal_n(state)).fold(Action::new(0, payoff(0)), |optimal, dice_rolled| {
+            let payoff = payoff(dice_rolled);
+            // If equal (up to rounding), the less aggressive move is taken.
+            if payoff > optimal.payoff() + tolerance {
+                Action::new(dice_rolled, payoff)
+            } else {
+                optimal
+            }
+        })
+    }
+    /// Find the optimal number of dice to roll in a terminal state of
+    /// `EndRule::LastTurn`.
+    pub fn find_optimal_terminal_action(&self, state: State) -> Action<T> {
+        self.optimal_action(state, |dice_rolled| {
+            self.calc_terminal_payoff(state, dice_rolled)
+        })
+    }
+    /// Find the optimal number of dice to roll in a normal state (or a
+    /// terminal state of `EndRule::BothStand`).
+    ///
+    /// # Prerequisites
+    ///
+    /// All reachable future states must already be solved.
+    pub fn find_optimal_normal_action(&self, state: State) -> Action<T> {
+        self.optimal_action(state, |dice_rolled| {
+            self.calc_normal_payoff(state, dice_rolled)
+        })
+    }
+    /// Calculate the expected payoff of rolling `dice_rolled` dice on the
+    /// final turn, by summing over every dice total.
+    #[must_use]
+    pub fn calc_terminal_payoff(&self, state: State, dice_rolled: u32) -> T {
+        if dice_rolled == 0 {
+            return self.compare(state);
+        }
+        let ruleset = self.handicap.seat(state.seat());
+        let pmfs = &self.pmfs[state.seat().index()];
+        (dice_rolled..=ruleset.sides() * dice_rolled).fold(T::ZERO, |acc, dice_total| {
+            let probability = pmfs.lookup(dice_rolled, dice_total);
+            let score = state.active() + dice_total;
+            match score.cmp(&ruleset.max()) {
+                Ordering::Greater => acc - probability, // bust
+                Ordering::Equal if ruleset.exact_win() => acc + probability,
+                _ => {
+                    acc + probability
+                        * self.compare(
+                            State::new(score, state.queued(), true).with_seat(state.seat()),
+                        )
+                }
+            }
+        })
+    }
+    /// Calculate the expected payoff of rolling `dice_rolled` dice in a
+    /// normal state (or a terminal state of `EndRule::BothStand`), by
+    /// summing over every dice total.
+    ///
+    /// Rolling 0 dice in a normal state hands the opponent (in the other
+    /// seat) its final-round state.
+    ///
+    /// # Prerequisites
+    ///
+    /// All reachable future states must already be solved.
+    #[must_use]
+    pub fn calc_normal_payoff(&self, state: State, dice_rolled: u32) -> T {
+        let opponent = state.seat().other();
+        if dice_rolled == 0 && state.last() {
+            return self.compare(state);
+        }
+        if dice_rolled == 0 {
+            let terminal = State::new(state.queued(), state.active(), true).with_seat(opponent);
+            return -self.policy.get(&terminal).payoff();
+        }
+        let ruleset = self.handicap.seat(state.seat());
+        let pmfs = &self.pmfs[state.seat().index()];
+        let headroom = ruleset.max() - state.active();
+        let bust = T::ONE - pmfs.cdf(dice_rolled, headroom);
+        (dice_rolled..=(ruleset.sides() * dice_rolled).min(headroom)).fold(
+            -bust,
+            |acc, dice_total| {
+                let probability = pmfs.lookup(dice_rolled, dice_total);
+                if ruleset.exact_win() && dice_total == headroom {
+                    return acc + probability;
+                }
+                let next = State::new(state.queued(), state.active() + dice_total, false)
+                    .with_seat(opponent);
+                acc - probability * self.policy.get(&next).payoff()
+            },
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DpSolver, Ruleset, Solver, TieRule};
+
+    #[test]
+    fn test_even_seats_match_dp() {
+        for end_rule in EndRule::ALL {
+            for exact_win in [false, true] {
+                let ruleset = Ruleset::new(24, 4)
+                    .with_end_rule(end_rule)
+                    .with_exact_win(exact_win)
+                    .with_max_dice(5);
+                let expected = DpSolver::from_ruleset(ruleset).policy();
+                let policy = HandicapSolver::new(ruleset).policy();
+                for (state, b) in expected.iter() {
+                    for seat in Seat::ALL {
+                        let a = policy.get(&state.with_seat(seat));
+                        assert_eq!(a.n(), b.n(), "{end_rule} {exact_win} {state:?} {seat}");
+                        assert!(
+                            (a.payoff() - b.payoff()).abs() < 1e-12,
+                            "{end_rule} {exact_win} {state:?} {seat}"
+                        );
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_swapping_seats_swaps_policies() {
+        let (strong, weak) = (Ruleset::new(30, 6), Ruleset::new(24, 4).with_max_dice(3));
+        let policy = HandicapSolver::new(Handicap::new(strong, weak)).policy();
+        let swapped = HandicapSolver::new(Handicap::new(weak, strong)).policy();
+        for seat in Seat::ALL {
+            let (max, other) = (strong.max(), weak.max());
+            let (max, other) = match seat {
+                Seat::First => (max, other),
+                Seat::Second => (other, max),
+            };
+            for last in [false, true] {
+                for active in 0..=max {
+                    for queued in 0..=other {
+                        let state = State::new(active, queued, last).with_seat(seat);
+                        let a = policy.get(&state);
+                        let b = swapped.get(&state.with_seat(seat.other()));
+                        assert_eq!(a.n(), b.n(), "{state:?}");
+                        assert!((a.payoff() - b.payoff()).abs() < 1e-12, "{state:?}");
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_second_player_wins_ties() {
+        let ruleset = Ruleset::new(30, 6).with_tie_rule(TieRule::SecondPlayer);
+        let policy = HandicapSolver::new(ruleset).policy();
+        // Level on 29 in the final round, the second player stands and wins,
+        // while the first player needs a 1.
+        let state = State::new(29, 29, true);
+        let second = policy.get(&state.with_seat(Seat::Second));
+        assert_eq!((second.n(), second.payoff()), (0, 1.0));
+        let first = policy.get(&state.with_seat(Seat::First));
+        assert_eq!(first.n(), 1);
+        assert!((first.payoff() - (1.0 - 5.0) / 6.0).abs() < 1e-12);
+
+        let start = policy.get(&State::new(0, 0, false)).payoff();
+        let draw = DpSolver::new(30, 6).policy().get(&State::new(0, 0, false));
+        assert!(start < draw.payoff());
+
+        // The same ties, valued directly.
+        let direct = HandicapSolver::new(Ruleset::new(30, 6))
+            .with_tie_payoff(-1.0)
+            .policy();
+        for seat in Seat::ALL {
+            for (state, a) in direct.seat(seat).iter() {
+                let b = policy.seat(seat).get(&state);
+                assert_eq!((a.n(), a.payoff()), (b.n(), b.payoff()), "{state:?} {seat}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_sudden_death_favors_the_larger_die() {
+        let (d4, d6) = (Ruleset::new(30, 4), Ruleset::new(30, 6));
+        let tie = TieRule::SuddenDeath;
+        let handicap = Handicap::new(d4.with_tie_rule(tie), d6.with_tie_rule(tie));
+        // Of the 24 pairs of rolls, the d4 wins 6, loses 14, and ties 4.
+        assert!((handicap.tie_payoff(Seat::First) - (6.0 - 14.0) / 20.0).abs() < 1e-12);
+        assert!((handicap.tie_payoff(Seat::Second) - (14.0 - 6.0) / 20.0).abs() < 1e-12);
+        assert_eq!(d6.with_tie_rule(tie).tie_payoff(), Some(0.0));
+    }
+
+    #[test]
+    fn test_unsupported_handicaps() {
+        // The seats may only differ in their maximum, sides and dice limit.
+        let (d4, d6) = (Ruleset::new(30, 4), Ruleset::new(30, 6));
+        assert!(Handicap::try_new(d4.with_max_dice(3), d6).is_ok());
+        assert!(Handicap::try_new(d4.with_komi(2), d6).is_err());
+        assert!(Handicap::try_new(d4, d6.with_tie_rule(TieRule::Stander)).is_err());
+
+        let soft = |ruleset: Ruleset| ruleset.with_bust_rule(BustRule::Reset);
+        assert!(HandicapSolver::try_new(Handicap::new(soft(d4), soft(d6))).is_err());
+        assert!(HandicapSolver::try_new(soft(d6)).is_err());
+        assert!(HandicapSolver::try_new(Handicap::new(d4, d6)).is_ok());
+    }
+
+    #[test]
+    fn test_handicaps_help_the_opponent() {
+        let start = State::new(0, 0, false);
+        let even = DpSolver::new(50, 6).policy().get(&start).payoff();
+        // A lower maximum, a coarser die (overshooting the maximum by more)
+        // and a cap on the dice each handicap the first player.
+        for first in [
+            Ruleset::new(45, 6),
+            Ruleset::new(50, 12),
+            Ruleset::new(50, 6).with_max_dice(2),
+        ] {
+            let handicap = Handicap::new(first, Ruleset::new(50, 6));
+            let policy = HandicapSolver::new(handicap).policy();
+            let payoff = policy.get(&handicap.initial_state()).payoff();
+            assert!(payoff < even, "{first:?}: {payoff} vs {even}");
+
+            let opening = policy.get(&start);
+            assert!(opening.n() <= first.max_dice().unwrap_or(u32::MAX));
+        }
+    }
+}
@@ -0,0 +1,173 @@
+//! Mixed-strategy equilibria of two-player zero-sum matrix games.
+
+use crate::Scalar;
+
+/// An equilibrium of a zero-sum matrix game.
+#[derive(Debug, Clone)]
+pub(crate) struct Equilibrium<T: Scalar> {
+    /// The row player's mixed strategy.
+    pub(crate) row: Vec<T>,
+    /// The column player's mixed strategy.
+    pub(crate) column: Vec<T>,
+    /// The row player's expected payoff.
+    pub(crate) value: T,
+}
+
+/// Solve the zero-sum game in which the row player receives `payoffs[i][j]`
+/// (and the column player loses it) when playing row `i` against column `j`.
+///
+/// With every payoff shifted by `shift` to be at least 1, the column player's
+/// strategy solves the linear program
+///
+/// ```text
+/// maximize Σ_j y_j  subject to  Σ_j (payoffs[i][j] + shift) y_j ≤ 1,  y ≥ 0
+/// ```
+///
+/// whose optimum is `1 / (value + shift)`, and the row player's strategy is
+/// its dual. The program starts feasible at `y = 0` and is solved by the
+/// simplex method with Bland's rule, which cannot cycle.
+///
+/// # Panics
+///
+/// Panics if `payoffs` has no rows or no columns.
+pub(crate) fn solve<T: Scalar>(payoffs: &[Vec<T>]) -> Equilibrium<T> {
+    assert!(
+        payoffs.first().is_some_and(|row| !row.is_empty()),
+        "a matrix game needs actions"
+    );
+    let (rows, columns) = (payoffs.len(), payoffs[0].len());
+    let shift = payoffs
+        .iter()
+        .flatten()
+        .fold(T::ZERO, |lowest, &payoff| match payoff < lowest {
+            true => payoff,
+            false => lowest,
+        });
+    let shift = T::ONE - shift;
+
+    // The tableau has a row per constraint, laid out as `y`, the slacks and
+    // the right-hand side, and the objective row (of reduced costs) last.
+    let width = columns + rows + 1;
+    let mut tableau: Vec<Vec<T>> = payoffs
+        .iter()
+        .enumerate()
+        .map(|(i, payoffs)| {
+            let mut row = vec![T::ZERO; width];
+            for (cell, &payoff) in row.iter_mut().zip(payoffs) {
+                *cell = payoff + shift;
+            }
+            row[columns + i] = T::ONE;
+            row[width - 1] = T::ONE;
+            row
+        })
+        .collect();
+    let mut objective = vec![T::ZERO; width];
+    for cost in &mut objective[..columns] {
+        *cost = -T::ONE;
+    }
+    let mut basis: Vec<usize> = (columns..columns + rows).collect();
+
+    let epsilon = T::from_f64(T::TIE_TOLERANCE);
+    // Bland's rule: the lowest improving column enters...
+    while let Some(entering) = (0..width - 1).find(|&c| objective[c] < -epsilon) {
+        // ...and the lowest basic variable among the tightest rows leaves.
+        let leaving = (0..rows)
+            .filter(|&r| tableau[r][entering] > epsilon)
+            .min_by(|&r, &s| {
+                let ratio = |r: usize| tableau[r][width - 1] / tableau[r][entering];
+                ratio(r)
+                    .partial_cmp(&ratio(s))
+                    .unwrap()
+                    .then(basis[r].cmp(&basis[s]))
+            })
+            .expect("the program is bounded, since every payoff is positive");
+
+        let pivot = tableau[leaving][entering];
+        for cell in &mut tableau[leaving] {
+            *cell = *cell / pivot;
+        }
+        let pivot_row = tableau[leaving].clone();
+        let eliminate = |row: &mut Vec<T>| {
+            let factor = row[entering];
+            if factor != T::ZERO {
+                for (cell, &pivot) in row.iter_mut().zip(&pivot_row) {
+                    *cell -= factor * pivot;
+                }
+            }
+        };
+        for (r, row) in tableau.iter_mut().enumerate() {
+            if r != leaving {
+                eliminate(row);
+            }
+        }
+        eliminate(&mut objective);
+        basis[leaving] = entering;
+    }
+
+    // Rounding can leave tiny negative probabilities, which are dropped.
+    let total = objective[width - 1];
+    let probability = |p: T| match p > T::ZERO {
+        true => p / total,
+        false => T::ZERO,
+    };
+    let mut column = vec![T::ZERO; columns];
+    for (r, &variable) in basis.iter().enumerate() {
+        if variable < columns {
+            column[variable] = probability(tableau[r][width - 1]);
+        }
+    }
+    let row = objective[columns..columns + rows]
+        .iter()
+        .map(|&dual| probability(dual))
+        .collect();
+    Equilibrium {
+        row,
+        column,
+        value: T::ONE / total - shift,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(a: &[f64], b: &[f64]) {
+        assert_eq!(a.len(), b.len());
+        for (a, b) in a.iter().zip(b) {
+            assert!((a - b).abs() < 1e-12, "{a} != {b}");
+        }
+    }
+
+    #[test]
+    fn test_mixed_equilibria() {
+        let pennies = solve(&[vec![1.0, -1.0], vec![-1.0, 1.0]]);
+        assert!(pennies.value.abs() < 1e-12);
+        assert_close(&pennies.row, &[0.5, 0.5]);
+        assert_close(&pennies.column, &[0.5, 0.5]);
+
+        let rps = [
+            vec![0.0, -1.0, 1.0],
+            vec![1.0, 0.0, -1.0],
+            vec![-1.0, 1.0, 0.0],
+        ];
+        let rps = solve(&rps);
+        assert!(rps.value.abs() < 1e-12);
+        assert_close(&rps.row, &[1.0 / 3.0; 3]);
+        assert_close(&rps.column, &[1.0 / 3.0; 3]);
+
+        // Each player makes the other indifferent: 2p - (1 - p) = -p + (1 - p).
+        let uneven = solve(&[vec![2.0, -1.0], vec![-1.0, 1.0]]);
+        assert!((uneven.value - 0.2).abs() < 1e-12);
+        assert_close(&uneven.row, &[0.4, 0.6]);
+        assert_close(&uneven.column, &[0.4, 0.6]);
+    }
+
+    #[test]
+    fn test_saddle_point() {
+        // The first row dominates, and against it the second column is best.
+        let game = solve(&[vec![3.0, 1.0, 2.0], vec![2.0, 0.0, 1.0]]);
+        assert!((game.value - 1.0).abs() < 1e-12);
+        assert_close(&game.row, &[1.0, 0.0]);
+        assert_close(&game.column, &[0.0, 1.0, 0.0]);
+    }
+}
@@ -1,4 +1,25 @@
+mod checkpoint;
+mod cube;
+#[allow(clippy::module_inception)]
 mod dp;
+mod handicap;
+mod matrix_game;
+mod multi;
 mod pmf;
+mod progress;
+mod push;
+mod simultaneous;
+mod stream;
+mod terminal;
+mod wavefront;
 
-pub use dp::DpSolver;
+pub use cube::CubeSolver;
+pub use dp::{DpSolver, MIN_TILE_HEIGHT, PMF_TOLERANCE, PMFLookup, SWEEPS_PER_SIDE};
+pub use handicap::HandicapSolver;
+pub use multi::MultiMaxSolver;
+pub use pmf::{PmfBackend, PmfError};
+pub use progress::{CancelToken, Phase, Progress, SolveError};
+pub use push::PushSolver;
+pub use simultaneous::SimultaneousSolver;
+pub use stream::StreamSolver;
+pub use terminal::TerminalTable;
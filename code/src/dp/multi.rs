@@ -0,0 +1,173 @@
+use rayon::prelude::*;
+
+use super::dp::DpSolver;
+use super::pmf::PmfBackend;
+use crate::{EndRule, Policy, Ruleset, Scalar, Solver, State};
+
+/// Solves the same die for many maximum scores at once.
+///
+/// Only the distances to the maximum and between the two scores matter: a
+/// roll busts exactly when it passes the headroom, and the winner is decided
+/// by comparing scores. So raising the maximum and both scores by `d` gives
+/// the same game,
+///
+/// `V(a, q; max) = V(a + d, q + d; max + d)`
+///
+/// and the policy for every smaller maximum is the corner `active, queued ≥ d`
+/// of the policy for the largest one. One solve (with its PMFs and terminal
+/// table) therefore covers every maximum score, and the rest is copying.
+///
+/// # Example
+///
+/// ```rust
+/// use greed::{MultiMaxSolver, State};
+///
+/// let policies = MultiMaxSolver::new([50, 75, 100], 6).solve();
+/// let (ruleset, policy) = &policies[0];
+/// assert_eq!(ruleset.max(), 50);
+/// println!("Opening: roll {} dice", policy.get(&State::new(0, 0, false)).n());
+/// ```
+#[derive(Debug, Clone)]
+pub struct MultiMaxSolver<T: Scalar = f64> {
+    /// The maximum scores to solve, in increasing order without repeats.
+    maxes: Vec<u32>,
+    /// The solver for the largest maximum score.
+    solver: DpSolver<T>,
+}
+
+impl MultiMaxSolver {
+    /// Create a solver for every maximum score in `maxes` with `sides`-sided
+    /// dice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `maxes` is empty.
+    #[must_use]
+    pub fn new(maxes: impl IntoIterator<Item = u32>, sides: u32) -> Self {
+        let mut maxes: Vec<u32> = maxes.into_iter().collect();
+        maxes.sort_unstable();
+        maxes.dedup();
+        let largest = *maxes.last().expect("at least one maximum score");
+        Self {
+            maxes,
+            solver: DpSolver::new(largest, sides),
+        }
+    }
+}
+
+impl<T: Scalar> MultiMaxSolver<T> {
+    /// Compute payoffs and probabilities in `U` instead.
+    #[must_use]
+    pub fn with_precision<U: Scalar>(self) -> MultiMaxSolver<U> {
+        MultiMaxSolver {
+            maxes: self.maxes,
+            solver: self.solver.with_precision(),
+        }
+    }
+    /// End every game according to `end_rule`. (Translating the scores does
+    /// not change when a stand ends the game, so every end rule works.)
+    #[must_use]
+    pub fn with_end_rule(self, end_rule: EndRule) -> Self {
+        Self {
+            solver: self.solver.with_end_rule(end_rule),
+            ..self
+        }
+    }
+    /// Generate the PMFs with `backend` instead of FFT.
+    #[must_use]
+    pub fn with_pmf_backend(self, backend: PmfBackend) -> Self {
+        Self {
+            solver: self.solver.with_pmf_backend(backend),
+            ..self
+        }
+    }
+    /// Returns the maximum scores to solve, in increasing order.
+    #[must_use]
+    pub fn maxes(&self) -> &[u32] {
+        &self.maxes
+    }
+    /// Solve the largest maximum score, then extract the policy of every
+    /// maximum score from it.
+    ///
+    /// Policies are returned in increasing order of maximum score.
+    #[must_use]
+    pub fn solve(self) -> Vec<(Ruleset, Policy<T>)> {
+        self.solve_map(|ruleset, policy| (ruleset, policy))
+    }
+    /// Like `solve`, but pass every policy to `f` as soon as it is extracted
+    /// and return the results instead.
+    ///
+    /// Only a few policies are held at once, so summaries of many maximum
+    /// scores fit in memory.
+    pub fn solve_map<R: Send>(mut self, f: impl Fn(Ruleset, Policy<T>) -> R + Sync) -> Vec<R> {
+        let largest = self.solver.policy();
+        let sides = self.solver.sides();
+        let end_rule = self.solver.ruleset().end_rule();
+        self.maxes
+            .par_iter()
+            .map(|&max| {
+                let ruleset = Ruleset::new(max, sides).with_end_rule(end_rule);
+                f(ruleset, translate(&largest, max))
+            })
+            .collect()
+    }
+}
+
+/// Extract the policy for the maximum score `max` from a policy for a larger
+/// maximum score, by shifting every state up by the difference.
+fn translate<T: Scalar>(policy: &Policy<T>, max: u32) -> Policy<T> {
+    let d = policy.max() - max;
+    let mut translated = Policy::new(max);
+    for last in [false, true] {
+        for queued in 0..=max {
+            for active in 0..=max {
+                let action = policy.get(&State::new(active + d, queued + d, last));
+                translated.set(&State::new(active, queued, last), action);
+            }
+        }
+    }
+    translated
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_translation_holds_for_every_end_rule() {
+        for end_rule in EndRule::ALL {
+            let policies = MultiMaxSolver::new([15, 25], 3)
+                .with_end_rule(end_rule)
+                .solve();
+            let (ruleset, policy) = &policies[0];
+            assert_eq!(ruleset.end_rule(), end_rule);
+
+            let expected = DpSolver::from_ruleset(*ruleset).policy();
+            for ((state, a), (_, b)) in policy.iter().zip(expected.iter()) {
+                assert_eq!(a.n(), b.n(), "{end_rule} {state:?}");
+                assert!(
+                    (a.payoff() - b.payoff()).abs() < 1e-12,
+                    "{end_rule} {state:?}"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_matches_separate_solves() {
+        let policies = MultiMaxSolver::new([30, 12, 45, 30], 4).solve();
+        let maxes: Vec<u32> = policies.iter().map(|(ruleset, _)| ruleset.max()).collect();
+        assert_eq!(maxes, [12, 30, 45]);
+
+        for (ruleset, policy) in policies {
+            let expected = DpSolver::new(ruleset.max(), ruleset.sides()).policy();
+            for ((state, a), (_, b)) in policy.iter().zip(expected.iter()) {
+                assert_eq!(a.n(), b.n(), "{ruleset:?} {state:?}");
+                assert!(
+                    (a.payoff() - b.payoff()).abs() < 1e-12,
+                    "{ruleset:?} {state:?}"
+                );
+            }
+        }
+    }
+}
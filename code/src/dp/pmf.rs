@@ -1,6 +1,285 @@
+use std::fmt;
+use std::str::FromStr;
+
 use rustfft::{FftPlanner, num_complex::Complex};
 
+use crate::Scalar;
+
+/// The algorithm used to generate the PMF of the sum of `n` dice.
+///
+/// Every backend builds the PMF of `n` dice from the PMF of `n - 1` dice.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum PmfBackend {
+    /// Convolve with one die using FFT. Fast, but every step adds rounding
+    /// noise (including small negative values, which are clamped to 0).
+    #[default]
+    Fft,
+    /// Convolve with one die directly, using compensated (Neumaier)
+    /// summation.
+    Compensated,
+    /// Convolve with one die directly in log space (log-sum-exp), which keeps
+    /// tiny tail probabilities from losing precision before the final
+    /// conversion.
+    LogSpace,
+    /// Count the ways to roll every total with exact integer arithmetic and
+    /// divide by `sides^n` at the end. Exact up to the final rounding, but the
+    /// slowest.
+    Exact,
+}
+
+impl PmfBackend {
+    /// Generate the PMFs of 0 to `max_n` dice.
+    ///
+    /// Returns the PMF rows (indexed from total `n`) together with an estimate
+    /// of the maximum absolute error of each row. Only the compensated backend
+    /// computes in `T`; the others compute in `f64` and convert.
+    pub(crate) fn generate<T: Scalar>(self, max_n: u32, sides: u32) -> (Vec<Vec<T>>, Vec<f64>) {
+        let mut pmfs: Vec<Vec<T>> = Vec::with_capacity(max_n as usize + 1);
+        let mut errors = Vec::with_capacity(max_n as usize + 1);
+        pmfs.push(vec![T::ONE]); // n=0 case
+        errors.push(0.0);
+
+        match self {
+            Self::Fft => {
+                let dice_pmf = vec![1.0 / f64::from(sides); sides as usize];
+                let mut previous = vec![1.0];
+                for _ in 1..=max_n {
+                    let raw = fft_convolve(&previous, &dice_pmf);
+                    // Negative values are pure rounding noise.
+                    let negative = raw.iter().fold(0.0, |acc: f64, &p| acc.max(-p));
+                    previous = raw.into_iter().map(|p| p.max(0.0)).collect();
+                    let pmf = convert(&previous);
+                    errors.push(estimate_error(&pmf).max(negative));
+                    pmfs.push(pmf);
+                }
+            }
+            Self::Compensated => {
+                for n in 1..=max_n as usize {
+                    let pmf = convolve_die(&pmfs[n - 1], sides, |terms| {
+                        neumaier(terms) / T::from_u32(sides)
+                    });
+                    errors.push(estimate_error(&pmf));
+                    pmfs.push(pmf);
+                }
+            }
+            Self::LogSpace => {
+                let log_sides = f64::from(sides).ln();
+                let mut log_pmf = vec![0.0];
+                for _ in 1..=max_n {
+                    log_pmf = convolve_die(&log_pmf, sides, |terms| log_sum_exp(terms) - log_sides);
+                    let pmf: Vec<T> = log_pmf.iter().map(|p| T::from_f64(p.exp())).collect();
+                    errors.push(estimate_error(&pmf));
+                    pmfs.push(pmf);
+                }
+            }
+            Self::Exact => {
+                let mut counts = vec![BigUint::from(1)];
+                let mut outcomes = BigUint::from(1);
+                for _ in 1..=max_n {
+                    counts = convolve_die(&counts, sides, |terms| {
+                        terms.iter().fold(BigUint::from(0), |acc, c| acc.add(c))
+                    });
+                    outcomes = outcomes.mul_small(u64::from(sides));
+                    let pmf: Vec<T> = counts
+                        .iter()
+                        .map(|c| T::from_f64(c.ratio(&outcomes)))
+                        .collect();
+                    errors.push(estimate_error(&pmf));
+                    pmfs.push(pmf);
+                }
+            }
+        }
+
+        (pmfs, errors)
+    }
+    /// Every backend.
+    pub const ALL: [Self; 4] = [Self::Fft, Self::Compensated, Self::LogSpace, Self::Exact];
+}
+
+impl fmt::Display for PmfBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Fft => "fft",
+            Self::Compensated => "compensated",
+            Self::LogSpace => "log",
+            Self::Exact => "exact",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for PmfBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|backend| backend.to_string() == s)
+            .ok_or_else(|| format!("unknown PMF backend `{s}`"))
+    }
+}
+
+/// The error returned when a generated PMF is less accurate than required.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub struct PmfError {
+    /// The backend that generated the PMF.
+    pub backend: PmfBackend,
+    /// The number of dice whose PMF exceeded the tolerance.
+    pub n: u32,
+    /// The estimated maximum absolute error of that PMF.
+    pub error: f64,
+    /// The tolerance that was exceeded.
+    pub tolerance: f64,
+}
+
+impl fmt::Display for PmfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "PMF for {} dice ({} backend) has an estimated error of {:e}, exceeding the tolerance of {:e}",
+            self.n, self.backend, self.error, self.tolerance
+        )
+    }
+}
+
+impl std::error::Error for PmfError {}
+
+/// Estimate the maximum absolute error of the PMF of a sum of dice.
+///
+/// The exact PMF sums to 1 and is symmetric about its mean, so the estimate is
+/// the largest violation of either property.
+pub(crate) fn estimate_error<T: Scalar>(pmf: &[T]) -> f64 {
+    let total = neumaier(pmf);
+    let asymmetry = pmf
+        .iter()
+        .zip(pmf.iter().rev())
+        .fold(0.0, |acc: f64, (&a, &b)| acc.max((a - b).abs().to_f64()));
+    (total - T::ONE).abs().to_f64().max(asymmetry)
+}
+
+/// Convert a row computed in `f64`.
+fn convert<T: Scalar>(row: &[f64]) -> Vec<T> {
+    row.iter().map(|&p| T::from_f64(p)).collect()
+}
+
+/// Convolve a row (indexed from total `n - 1`) with one die, combining the
+/// `sides` contributing terms of every total with `combine`.
+fn convolve_die<T: Clone>(previous: &[T], sides: u32, combine: impl Fn(&[T]) -> T) -> Vec<T> {
+    let len = previous.len() + sides as usize - 1;
+    (0..len)
+        .map(|i| {
+            // Total n + i is reached from totals n - 1 + j with j in
+            // i + 1 - sides..=i.
+            let lo = (i + 1).saturating_sub(sides as usize);
+            let hi = i.min(previous.len() - 1);
+            combine(&previous[lo..=hi])
+        })
+        .collect()
+}
+
+/// Sum with Neumaier's compensated summation.
+pub(crate) fn neumaier<T: Scalar>(terms: &[T]) -> T {
+    let (sum, compensation) = terms.iter().fold((T::ZERO, T::ZERO), |(sum, c), &x| {
+        let t = sum + x;
+        let c = if sum.abs() >= x.abs() {
+            c + ((sum - t) + x)
+        } else {
+            c + ((x - t) + sum)
+        };
+        (t, c)
+    });
+    sum + compensation
+}
+
+/// Compute ln(Σ exp(x)) without overflow or underflow.
+fn log_sum_exp(terms: &[f64]) -> f64 {
+    let max = terms.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    if max == f64::NEG_INFINITY {
+        return max;
+    }
+    max + terms.iter().map(|x| (x - max).exp()).sum::<f64>().ln()
+}
+
+/// A minimal arbitrary-precision unsigned integer (little-endian limbs), just
+/// enough to count dice outcomes exactly.
+#[derive(Debug, Clone)]
+struct BigUint {
+    limbs: Vec<u64>,
+}
+
+impl From<u64> for BigUint {
+    fn from(value: u64) -> Self {
+        Self { limbs: vec![value] }
+    }
+}
+
+impl BigUint {
+    /// Returns `self + other`.
+    fn add(&self, other: &Self) -> Self {
+        let (long, short) = if self.limbs.len() >= other.limbs.len() {
+            (self, other)
+        } else {
+            (other, self)
+        };
+        let mut limbs = Vec::with_capacity(long.limbs.len() + 1);
+        let mut carry = false;
+        for (i, &limb) in long.limbs.iter().enumerate() {
+            let (sum, c1) = limb.overflowing_add(*short.limbs.get(i).unwrap_or(&0));
+            let (sum, c2) = sum.overflowing_add(u64::from(carry));
+            limbs.push(sum);
+            carry = c1 || c2;
+        }
+        if carry {
+            limbs.push(1);
+        }
+        Self { limbs }
+    }
+    /// Returns `self × factor`.
+    fn mul_small(&self, factor: u64) -> Self {
+        let mut limbs = Vec::with_capacity(self.limbs.len() + 1);
+        let mut carry = 0;
+        for &limb in &self.limbs {
+            let product = u128::from(limb) * u128::from(factor) + carry;
+            limbs.push(product as u64);
+            carry = product >> 64;
+        }
+        if carry > 0 {
+            limbs.push(carry as u64);
+        }
+        Self { limbs }
+    }
+    /// Returns `(mantissa, exponent)` with `self ≈ mantissa × 2^exponent`,
+    /// where the mantissa holds the top 64 bits.
+    fn to_float_parts(&self) -> (f64, i32) {
+        let top = self.limbs.iter().rposition(|&limb| limb != 0);
+        let Some(top) = top else {
+            return (0.0, 0);
+        };
+        let shift = self.limbs[top].leading_zeros();
+        let high = u128::from(self.limbs[top]) << 64
+            | u128::from(if top > 0 { self.limbs[top - 1] } else { 0 });
+        let mantissa = (high << shift >> 64) as u64;
+        let exponent = 64 * top as i32 - shift as i32;
+        (mantissa as f64, exponent)
+    }
+    /// Returns `self / other` rounded to an `f64`.
+    fn ratio(&self, other: &Self) -> f64 {
+        let (a, ea) = self.to_float_parts();
+        let (b, eb) = other.to_float_parts();
+        if a == 0.0 {
+            return 0.0;
+        }
+        // Split the power of two so that neither factor overflows.
+        let exponent = ea - eb;
+        let half = exponent / 2;
+        (a / b) * 2f64.powi(half) * 2f64.powi(exponent - half)
+    }
+}
+
 /// Convolve two real-valued PMFs using FFT.
+///
+/// The result is not clamped, so it may contain tiny negative values from
+/// rounding.
 #[must_use]
 pub fn fft_convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
     let size = (a.len() + b.len()).next_power_of_two();
@@ -22,5 +301,41 @@ pub fn fft_convolve(a: &[f64], b: &[f64]) -> Vec<f64> {
 
     ifft.process(&mut fa);
     fa.truncate(a.len() + b.len() - 1);
-    fa.iter().map(|x| (x.re / size as f64).max(0.0)).collect()
+    fa.iter().map(|x| x.re / size as f64).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backends_agree() {
+        let (exact, errors) = PmfBackend::Exact.generate::<f64>(40, 6);
+        assert!(errors.iter().all(|&e| e < 1e-14), "{errors:?}");
+        // P(sum of 2d6 = 7) = 6/36
+        assert!((exact[2][7 - 2] - 6.0 / 36.0).abs() < 1e-16);
+
+        for backend in [
+            PmfBackend::Fft,
+            PmfBackend::Compensated,
+            PmfBackend::LogSpace,
+        ] {
+            let (pmfs, _) = backend.generate::<f64>(40, 6);
+            for (row, expected) in pmfs.iter().zip(&exact) {
+                assert_eq!(row.len(), expected.len());
+                for (p, q) in row.iter().zip(expected) {
+                    assert!((p - q).abs() < 1e-13, "{backend}: {p} != {q}");
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_exact_counts_past_u128() {
+        // 6^100 ≈ 6.5e77 outcomes overflows u128, but every PMF stays exact.
+        let (pmfs, _) = PmfBackend::Exact.generate::<f64>(100, 6);
+        assert!((pmfs[100][0] - 6f64.powi(-100)).abs() <= 6f64.powi(-100) * 1e-15);
+        let total = neumaier(&pmfs[100]);
+        assert!((total - 1.0).abs() < 1e-15);
+    }
 }
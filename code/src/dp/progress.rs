@@ -0,0 +1,166 @@
+use std::fmt;
+use std::io;
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+/// A stage of `DpSolver::solve`.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Phase {
+    /// Precomputing the dice PMFs.
+    Pmfs,
+    /// Solving (or reusing) the terminal states.
+    Terminal,
+    /// Solving the normal states, order by order.
+    Normal,
+}
+
+impl fmt::Display for Phase {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Pmfs => "pmf",
+            Self::Terminal => "terminal",
+            Self::Normal => "normal",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A snapshot of how far a solve has come, reported after every step.
+///
+/// The PMF and terminal phases are single steps; the normal phase reports
+/// once per order (active + queued), from `2 × max` down to 0, as each tile of
+/// orders finishes.
+#[derive(Debug, Copy, Clone)]
+pub struct Progress {
+    /// The current phase.
+    phase: Phase,
+    /// The order just solved (normal phase only).
+    order: Option<u32>,
+    /// The largest order, `2 × max`.
+    orders: u32,
+    /// The units of work done in this phase.
+    done: u64,
+    /// The units of work in this phase.
+    total: u64,
+    /// The time spent in this phase so far.
+    elapsed: Duration,
+}
+
+impl Progress {
+    /// Create a progress report.
+    pub(crate) fn new(
+        phase: Phase,
+        order: Option<u32>,
+        orders: u32,
+        (done, total): (u64, u64),
+        elapsed: Duration,
+    ) -> Self {
+        Self {
+            phase,
+            order,
+            orders,
+            done,
+            total,
+            elapsed,
+        }
+    }
+    /// Get the current phase.
+    #[must_use]
+    pub fn phase(&self) -> Phase {
+        self.phase
+    }
+    /// Get the order just solved, if in the normal phase.
+    #[must_use]
+    pub fn order(&self) -> Option<u32> {
+        self.order
+    }
+    /// Get the largest order, `2 × max`.
+    #[must_use]
+    pub fn orders(&self) -> u32 {
+        self.orders
+    }
+    /// Get the fraction of the current phase that is done.
+    ///
+    /// In the normal phase this is the fraction of states solved (orders near
+    /// `max` hold the most states).
+    #[must_use]
+    pub fn fraction(&self) -> f64 {
+        if self.total == 0 {
+            1.0
+        } else {
+            self.done as f64 / self.total as f64
+        }
+    }
+    /// Get the time spent in the current phase so far.
+    #[must_use]
+    pub fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+    /// Estimate the time left in the current phase, assuming the remaining
+    /// work proceeds at the average rate so far.
+    #[must_use]
+    pub fn eta(&self) -> Option<Duration> {
+        (self.done > 0).then(|| {
+            self.elapsed
+                .mul_f64((self.total - self.done) as f64 / self.done as f64)
+        })
+    }
+}
+
+/// A shared flag that asks a running solve to stop.
+///
+/// The solver checks it between phases and between tiles of orders, so a
+/// cancelled solve stops within one tile (see `DpSolver::with_tile_height`).
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    /// Create a token that is not cancelled.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Ask every solve holding this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+    /// Returns whether the token has been cancelled.
+    #[must_use]
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The error returned by a solve that did not finish.
+#[derive(Debug)]
+pub enum SolveError {
+    /// The solve was cancelled through its `CancelToken`.
+    Cancelled,
+    /// A checkpoint could not be written.
+    Checkpoint(io::Error),
+    /// The value iteration of a soft `BustRule` did not converge within this
+    /// many sweeps.
+    NotConverged(u32),
+}
+
+impl fmt::Display for SolveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Cancelled => f.write_str("solve was cancelled"),
+            Self::Checkpoint(error) => write!(f, "failed to write checkpoint: {error}"),
+            Self::NotConverged(sweeps) => {
+                write!(f, "soft busts did not converge within {sweeps} sweeps")
+            }
+        }
+    }
+}
+
+impl std::error::Error for SolveError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Cancelled | Self::NotConverged(_) => None,
+            Self::Checkpoint(error) => Some(error),
+        }
+    }
+}
@@ -0,0 +1,358 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+
+use crate::{
+    Action, BustRule, EndRule, Policy, PushPolicy, Ruleset, Scalar, State, UnsupportedRuleset,
+};
+
+/// Solves one-die-at-a-time Greed, where a turn rolls a single die at a time
+/// and decides after each roll whether to keep going.
+///
+/// The dice rolled so far are already known, so a player can do at least as
+/// well as by committing to `n` dice up front, which is what `DpSolver`
+/// solves. Midway through a turn, only the current score matters (and
+/// whether anything has been rolled yet, see `PushState`), so the policy
+/// splits into turn-start and mid-turn tables with the same layout as a
+/// `Policy`:
+///
+/// + `V(a, q)` at the start of a turn stands (see `EndRule`) or rolls;
+/// + `U(x, q)` after rolling to a score of `x` stops, handing the opponent
+///   `V(q, x)`, or rolls again, and
+/// + rolling from either is worth `(1/sides) × Σ_{k=1..=sides} U(x + k, q)`
+///   (-1 past the maximum).
+///
+/// Terminal states first, then normal states by decreasing order, as for
+/// `DpSolver`: within an order, `V` only needs higher orders and the
+/// terminal states, and `U(x, q)` needs `V(q, x)` of the same order, so `V`
+/// is solved before `U`.
+///
+/// # Example
+///
+/// ```rust
+/// use greed::{PushSolver, PushState, State};
+///
+/// let policy = PushSolver::new(100, 6).policy();
+/// // Keep rolling on 40 from the start of a turn?
+/// let state = PushState::new(State::new(0, 0, false), 40);
+/// println!("Roll again: {}", policy.get(&state).n() == 1);
+/// ```
+#[derive(Debug, Clone)]
+pub struct PushSolver<T: Scalar = f64> {
+    /// Game configuration (maximum score and die sides).
+    ruleset: Ruleset,
+    /// Computed optimal policy.
+    policy: PushPolicy<T>,
+}
+
+impl PushSolver {
+    /// Create a new solver for the specified game parameters.
+    #[must_use]
+    pub fn new(max: u32, sides: u32) -> Self {
+        Self::from_ruleset(Ruleset::new(max, sides))
+    }
+    /// Create a new solver for a ruleset (including its end rule and exact
+    /// wins).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ruleset is not supported (see `try_from_ruleset`).
+    #[must_use]
+    pub fn from_ruleset(ruleset: Ruleset) -> Self {
+        Self::try_from_ruleset(ruleset).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Create a new solver for a ruleset, if its turns can be played one die
+    /// at a time.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the ruleset has soft busts (see `BustRule`) or
+    /// limits the dice per turn, which would make the subtotal (or the dice
+    /// rolled so far) part of the state, or lets the second player win ties,
+    /// which would make the seat part of it (see `TieRule`).
+    pub fn try_from_ruleset(ruleset: Ruleset) -> Result<Self, UnsupportedRuleset> {
+        if ruleset.bust_rule() != BustRule::Lose || ruleset.max_dice().is_some() {
+            return Err(UnsupportedRuleset {
+                reason: "one-die-at-a-time turns need BustRule::Lose and no dice limit",
+            });
+        }
+        match ruleset.tie_payoff() {
+            Some(_) => Ok(Self::with_ruleset(ruleset)),
+            None => Err(UnsupportedRuleset {
+                reason: "the second player winning ties depends on the seat",
+            }),
+        }
+    }
+}
+
+impl<T: Scalar> PushSolver<T> {
+    /// Create a new solver (computing in `T`) for a supported ruleset.
+    fn with_ruleset(ruleset: Ruleset) -> Self {
+        PushSolver {
+            ruleset,
+            policy: PushPolicy::new(ruleset.max()),
+        }
+    }
+    /// Compute payoffs in `U` instead.
+    #[must_use]
+    pub fn with_precision<U: Scalar>(self) -> PushSolver<U> {
+        PushSolver::with_ruleset(self.ruleset)
+    }
+    /// Returns the ruleset used by the solver.
+    #[must_use]
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+    /// Solve the game, and return its policy.
+    pub fn policy(&mut self) -> PushPolicy<T> {
+        self.solve();
+        self.policy.clone()
+    }
+    /// Consume the solver, returning its policy (as solved so far).
+    #[must_use]
+    pub fn into_policy(self) -> PushPolicy<T> {
+        self.policy
+    }
+    /// Compute the complete optimal policy.
+    ///
+    /// With `EndRule::LastTurn`, the final round is a single turn against a
+    /// fixed score, so its mid-turn states are solved first, by decreasing
+    /// score. With `EndRule::Immediate`, a terminal state is a finished game.
+    /// With `EndRule::BothStand`, a terminal state rolls back into normal
+    /// play, so the terminal states of each order are solved just before its
+    /// normal states.
+    pub fn solve(&mut self) {
+        let max = self.ruleset.max();
+        let end_rule = self.ruleset.end_rule();
+        match end_rule {
+            EndRule::LastTurn => {
+                let solver = &*self;
+                let columns: Vec<Policy<T>> = (0..=max)
+                    .into_par_iter()
+                    .map(|queued| {
+                        // Only this opponent score is read, so a column needs
+                        // nothing else.
+                        let mut column = Policy::new(max);
+                        for score in (0..=max).rev() {
+                            let state = State::new(score, queued, true);
+                            let roll = solver.calc_roll_payoff(&column, state, true);
+                            column.set(&state, solver.choose(solver.compare(state), roll));
+                        }
+                        column
+                    })
+                    .collect();
+                for (queued, column) in (0..=max).zip(columns) {
+                    for score in 0..=max {
+                        let state = State::new(score, queued, true);
+                        let action = column.get(&state);
+                        self.policy.turn_start_mut().set(&state, action);
+                        self.policy.mid_turn_mut().set(&state, action);
+                    }
+                }
+            }
+            EndRule::Immediate => {
+                for active in 0..=max {
+                    for queued in 0..=max {
+                        let state = State::new(active, queued, true);
+                        let action = Action::new(0, self.compare(state));
+                        self.policy.turn_start_mut().set(&state, action);
+                    }
+                }
+            }
+            EndRule::BothStand => {}
+        }
+
+        for order in (0..=2 * max).rev() {
+            if end_rule == EndRule::BothStand {
+                self.solve_order(order, true, false, |solver, state| {
+                    let roll = solver.calc_roll_payoff(solver.policy.mid_turn(), state, false);
+                    solver.choose(solver.compare(state), roll)
+                });
+            }
+            self.solve_order(order, false, false, |solver, state| {
+                let terminal = State::new(state.queued(), state.active(), true);
+                let stand = -solver.policy.turn_start().get(&terminal).payoff();
+                let roll = solver.calc_roll_payoff(solver.policy.mid_turn(), state, false);
+                solver.choose(stand, roll)
+            });
+            self.solve_order(order, false, true, |solver, state| {
+                let next = State::new(state.queued(), state.active(), false);
+                let stop = -solver.policy.turn_start().get(&next).payoff();
+                let roll = solver.calc_roll_payoff(solver.policy.mid_turn(), state, false);
+                solver.choose(stop, roll)
+            });
+        }
+    }
+    /// Solve every state of `order` in the final-round layer `last` with
+    /// `solve`, storing the actions mid-turn if `mid_turn`, and at the start
+    /// of a turn otherwise.
+    fn solve_order(
+        &mut self,
+        order: u32,
+        last: bool,
+        mid_turn: bool,
+        solve: impl Fn(&Self, State) -> Action<T> + Sync,
+    ) {
+        let max = self.ruleset.max();
+        let solver = &*self;
+        let actions: Vec<(State, Action<T>)> = (order.saturating_sub(max)..=order.min(max))
+            .into_par_iter()
+            .map(|queued| {
+                let state = State::new(order - queued, queued, last);
+                (state, solve(solver, state))
+            })
+            .collect();
+        let table = match mid_turn {
+            true => self.policy.mid_turn_mut(),
+            false => self.policy.turn_start_mut(),
+        };
+        for (state, action) in actions {
+            table.set(&state, action);
+        }
+    }
+    /// The payoff of ending the game in `state`: comparing the scores (see
+    /// `TieRule`).
+    fn compare(&self, state: State) -> T {
+        match state.active().cmp(&state.queued()) {
+            Ordering::Less => -T::ONE,
+            Ordering::Equal => {
+                let tie = self.ruleset.tie_payoff();
+                T::from_f64(tie.expect("checked when the solver was created"))
+            }
+            Ordering::Greater => T::ONE,
+        }
+    }
+    /// Stop (worth `stop`) unless rolling (worth `roll`) is better, breaking
+    /// ties (up to rounding) toward stopping.
+    fn choose(&self, stop: T, roll: T) -> Action<T> {
+        match roll > stop + T::from_f64(T::TIE_TOLERANCE) {
+            true => Action::new(1, roll),
+            false => Action::new(0, stop),
+        }
+    }
+    /// Calculate the expected payoff of rolling one more die on the score
+    /// `state.active()`, continuing in the mid-turn states of `mid_turn` in
+    /// the layer `last`.
+    fn calc_roll_payoff(&self, mid_turn: &Policy<T>, state: State, last: bool) -> T {
+        let (max, sides) = (self.ruleset.max(), self.ruleset.sides());
+        let total = (1..=sides).fold(T::ZERO, |acc, face| {
+            let score = state.active() + face;
+            match score.cmp(&max) {
+                Ordering::Greater => acc - T::ONE, // bust
+                Ordering::Equal if self.ruleset.exact_win() => acc + T::ONE,
+                _ => {
+                    acc + mid_turn
+                        .get(&State::new(score, state.queued(), last))
+                        .payoff()
+                }
+            }
+        });
+        total / T::from_f64(f64::from(sides))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::{DpSolver, Solver, TieRule};
+
+    /// The payoff of the mover on `score` against `queued`, by searching the
+    /// game tree turn by turn (`rolled` is whether the turn has rolled yet).
+    fn search(
+        ruleset: Ruleset,
+        memo: &mut HashMap<(u32, u32, bool, bool), f64>,
+        (score, queued, last, rolled): (u32, u32, bool, bool),
+    ) -> f64 {
+        let key = (score, queued, last, rolled);
+        if let Some(&payoff) = memo.get(&key) {
+            return payoff;
+        }
+        let ends = match ruleset.end_rule() {
+            EndRule::LastTurn => last,
+            EndRule::Immediate => !rolled,
+            EndRule::BothStand => last && !rolled,
+        };
+        let stop = match ends {
+            true => match score.cmp(&queued) {
+                Ordering::Less => -1.0,
+                Ordering::Equal => ruleset.tie_payoff().unwrap(),
+                Ordering::Greater => 1.0,
+            },
+            false => -search(ruleset, memo, (queued, score, !rolled, false)),
+        };
+        if last && ruleset.end_rule() == EndRule::Immediate {
+            return stop; // the game is over
+        }
+        let sides = ruleset.sides();
+        let roll: f64 = (1..=sides)
+            .map(|face| match score + face {
+                next if next > ruleset.max() => -1.0,
+                next if next == ruleset.max() && ruleset.exact_win() => 1.0,
+                next => {
+                    let last = last && ruleset.end_rule() == EndRule::LastTurn;
+                    search(ruleset, memo, (next, queued, last, true))
+                }
+            })
+            .sum::<f64>()
+            / f64::from(sides);
+        let payoff = if roll > stop { roll } else { stop };
+        memo.insert(key, payoff);
+        payoff
+    }
+
+    #[test]
+    fn test_matches_game_tree_search() {
+        for end_rule in EndRule::ALL {
+            for exact_win in [false, true] {
+                let ruleset = Ruleset::new(12, 3)
+                    .with_end_rule(end_rule)
+                    .with_exact_win(exact_win);
+                let policy = PushSolver::from_ruleset(ruleset).policy();
+                let mut memo = HashMap::new();
+                for (state, action) in policy.turn_start().iter() {
+                    let (a, q, last) = (state.active(), state.queued(), state.last());
+                    let expected = search(ruleset, &mut memo, (a, q, last, false));
+                    assert!(
+                        (action.payoff() - expected).abs() < 1e-12,
+                        "{end_rule} {exact_win} {state:?}"
+                    );
+                    if a == 0 || (last && end_rule != EndRule::LastTurn) {
+                        continue; // no turn is ever midway here
+                    }
+                    let expected = search(ruleset, &mut memo, (a, q, last, true));
+                    let payoff = policy.mid_turn().get(&state).payoff();
+                    assert!(
+                        (payoff - expected).abs() < 1e-12,
+                        "{end_rule} {exact_win} {state:?} mid-turn"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_final_round_is_never_worse_than_committing() {
+        let ruleset = Ruleset::new(40, 6);
+        let commit = DpSolver::from_ruleset(ruleset).policy();
+        let push = PushSolver::from_ruleset(ruleset).policy();
+        for (state, action) in commit.iter().filter(|(state, _)| state.last()) {
+            let payoff = push.turn_start().get(&state).payoff();
+            assert!(payoff >= action.payoff() - 1e-12, "{state:?}");
+        }
+    }
+
+    #[test]
+    fn test_unsupported_rulesets() {
+        let ruleset = Ruleset::new(30, 6);
+        assert!(PushSolver::try_from_ruleset(ruleset).is_ok());
+        for unsupported in [
+            ruleset.with_bust_rule(BustRule::Reset),
+            ruleset.with_max_dice(3),
+            ruleset.with_tie_rule(TieRule::SecondPlayer),
+        ] {
+            assert!(PushSolver::try_from_ruleset(unsupported).is_err());
+        }
+    }
+}
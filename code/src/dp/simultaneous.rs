@@ -0,0 +1,237 @@
+use std::cmp::Ordering;
+
+use rayon::prelude::*;
+
+use super::dp::PMFLookup;
+use super::matrix_game;
+use crate::{BustRule, EndRule, MixedAction, MixedPolicy, Ruleset, Scalar, State};
+
+/// Solves simultaneous Greed, where both players secretly choose a number of
+/// dice each round, reveal them at once, and roll.
+///
+/// A player who busts loses (both busting is a draw). Otherwise, the round is
+/// the last if either player stood (rolled 0 dice), and the scores are
+/// compared as usual; if both rolled, play goes on from the new scores.
+///
+/// Each state `(own, opponent)` is then a zero-sum matrix game between the
+/// two dice counts, which can have only a mixed equilibrium: a player who
+/// always stood on 80 could be beaten by an opponent on 75 who knew it. Its
+/// equilibrium is found by linear programming, and the policy stores a
+/// probability for each dice count (see `MixedPolicy`).
+///
+/// When both players roll, both scores increase, so the states are solved by
+/// decreasing order (`own + opponent`), as for `DpSolver`. The game is
+/// symmetric, so the equilibrium of `(a, b)` also gives that of `(b, a)`:
+/// the opponent's strategy and the negated payoff.
+///
+/// # Example
+///
+/// ```rust
+/// use greed::{SimultaneousSolver, State};
+///
+/// let policy = SimultaneousSolver::new(30, 6).policy();
+/// let opening = policy.get(&State::new(0, 0, false));
+/// for n in opening.support() {
+///     println!("roll {n} dice with probability {:.3}", opening.probability(n));
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SimultaneousSolver<T: Scalar = f64> {
+    /// Game configuration (maximum score and die sides).
+    ruleset: Ruleset,
+    /// Computed equilibrium policy.
+    policy: MixedPolicy<T>,
+    /// Precomputed probability mass functions for dice rolls.
+    pmfs: PMFLookup<T>,
+}
+
+impl SimultaneousSolver {
+    /// Create a new solver for the specified game parameters.
+    #[must_use]
+    pub fn new(max: u32, sides: u32) -> Self {
+        Self::from_ruleset(Ruleset::new(max, sides))
+    }
+    /// Create a new solver for a ruleset (including its komi and dice limit).
+    ///
+    /// # Panics
+    ///
+    /// Panics if the ruleset has another end rule (a stand always ends the
+    /// game after the round), soft busts, exact wins, or ties that are not
+    /// even (see `TieRule`), none of which are solved for simultaneous play.
+    #[must_use]
+    pub fn from_ruleset(ruleset: Ruleset) -> Self {
+        Self::with_ruleset(ruleset)
+    }
+}
+
+impl<T: Scalar> SimultaneousSolver<T> {
+    /// Create a new solver (computing in `T`) for a ruleset.
+    fn with_ruleset(ruleset: Ruleset) -> Self {
+        assert!(
+            ruleset.end_rule() == EndRule::default()
+                && ruleset.bust_rule() == BustRule::Lose
+                && !ruleset.exact_win(),
+            "simultaneous play needs the default end rule, BustRule::Lose and no exact wins"
+        );
+        assert!(
+            ruleset.tie_payoff() == Some(0.0),
+            "simultaneous play needs even ties"
+        );
+        SimultaneousSolver {
+            ruleset,
+            policy: MixedPolicy::new(ruleset.max()),
+            pmfs: PMFLookup::default(),
+        }
+    }
+    /// Compute payoffs and probabilities in `U` instead.
+    #[must_use]
+    pub fn with_precision<U: Scalar>(self) -> SimultaneousSolver<U> {
+        SimultaneousSolver::with_ruleset(self.ruleset)
+    }
+    /// Returns the ruleset used by the solver.
+    #[must_use]
+    pub fn ruleset(&self) -> Ruleset {
+        self.ruleset
+    }
+    /// Solve the game, and return its equilibrium policy.
+    pub fn policy(&mut self) -> MixedPolicy<T> {
+        self.solve();
+        self.policy.clone()
+    }
+    /// Consume the solver, returning its policy (as solved so far).
+    #[must_use]
+    pub fn into_policy(self) -> MixedPolicy<T> {
+        self.policy
+    }
+    /// Compute the equilibrium of every state, by decreasing order.
+    ///
+    /// # Panics
+    ///
+    /// Panics (in release builds too) if any PMF's estimated error exceeds
+    /// `T::PMF_TOLERANCE`.
+    pub fn solve(&mut self) {
+        let max = self.ruleset.max();
+        self.pmfs = PMFLookup::precompute(max, self.ruleset.sides());
+        for order in (0..=2 * max).rev() {
+            // Only `own >= opponent` is solved; the mirror image comes free.
+            let solver = &*self;
+            let equilibria: Vec<_> = (order.div_ceil(2)..=order.min(max))
+                .into_par_iter()
+                .map(|own| {
+                    let state = State::new(own, order - own, false);
+                    (state, matrix_game::solve(&solver.matrix(state)))
+                })
+                .collect();
+            for (state, equilibrium) in equilibria {
+                let mirror = State::new(state.queued(), state.active(), false);
+                if mirror != state {
+                    let action = MixedAction::new(equilibrium.column, -equilibrium.value);
+                    self.policy.set(&mirror, action);
+                }
+                let action = MixedAction::new(equilibrium.row, equilibrium.value);
+                self.policy.set(&state, action);
+            }
+        }
+    }
+    /// The largest number of dice worth considering (and allowed) on
+    /// `score`, as for `DpSolver`.
+    fn max_optimal_n(&self, score: u32) -> u32 {
+        let (max, sides) = (self.ruleset.max(), self.ruleset.sides());
+        let max_optimal_n = 2 * (max - score + sides) / (sides + 1);
+        self.ruleset
+            .max_dice()
+            .map_or(max_optimal_n, |max_dice| max_optimal_n.min(max_dice))
+    }
+    /// The probability of busting with `n` dice on `score`.
+    fn bust(&self, n: u32, score: u32) -> T {
+        match n {
+            0 => T::ZERO,
+            _ => T::ONE - self.pmfs.cdf(n, self.ruleset.max() - score),
+        }
+    }
+    /// Build the matrix game of `state`: the payoff to the player on
+    /// `state.active()` of rolling `i` dice (the row) while the opponent
+    /// rolls `j` (the column).
+    ///
+    /// # Prerequisites
+    ///
+    /// All states of higher order must already be solved.
+    fn matrix(&self, state: State) -> Vec<Vec<T>> {
+        let (own, opponent) = (state.active(), state.queued());
+        let (max, sides) = (self.ruleset.max(), self.ruleset.sides());
+        let (rows, columns) = (self.max_optimal_n(own), self.max_optimal_n(opponent));
+        let compare = |own: u32, opponent: u32| match own.cmp(&opponent) {
+            Ordering::Less => -T::ONE,
+            Ordering::Equal => T::ZERO,
+            Ordering::Greater => T::ONE,
+        };
+        // The dice totals of `n` dice on `score` that do not bust.
+        let totals = |n: u32, score: u32| n..=(sides * n).min(max - score);
+
+        let mut matrix = vec![vec![T::ZERO; columns as usize + 1]; rows as usize + 1];
+        matrix[0][0] = compare(own, opponent);
+        for j in 1..=columns {
+            matrix[0][j as usize] = totals(j, opponent).fold(self.bust(j, opponent), |acc, y| {
+                acc + self.pmfs.lookup(j, y) * compare(own, opponent + y)
+            });
+        }
+        for i in 1..=rows {
+            matrix[i as usize][0] = totals(i, own).fold(-self.bust(i, own), |acc, x| {
+                acc + self.pmfs.lookup(i, x) * compare(own + x, opponent)
+            });
+        }
+        for j in 1..=columns {
+            // The expected payoff of landing on each own score above `own`
+            // when the opponent rolls `j` dice and does not bust.
+            let landed: Vec<T> = (own + 1..=max)
+                .map(|score| {
+                    totals(j, opponent).fold(T::ZERO, |acc, y| {
+                        let next = State::new(score, opponent + y, false);
+                        acc + self.pmfs.lookup(j, y) * self.policy.get(&next).payoff()
+                    })
+                })
+                .collect();
+            let opponent_busts = self.bust(j, opponent);
+            for i in 1..=rows {
+                // Busting loses unless the opponent busts too.
+                let own_busts = self.bust(i, own);
+                let busts =
+                    opponent_busts * (T::ONE - own_busts) - own_busts * (T::ONE - opponent_busts);
+                matrix[i as usize][j as usize] = totals(i, own).fold(busts, |acc, x| {
+                    acc + self.pmfs.lookup(i, x) * landed[(x - 1) as usize]
+                });
+            }
+        }
+        matrix
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_equilibria() {
+        let mut solver = SimultaneousSolver::new(20, 4);
+        solver.solve();
+        for (state, action) in solver.policy.iter() {
+            // The game is symmetric.
+            let mirror = State::new(state.queued(), state.active(), false);
+            assert!((action.payoff() + solver.policy.get(&mirror).payoff()).abs() < 1e-12);
+
+            // No pure action does better against the opponent's strategy (and
+            // the opponent's check is the mirror image's).
+            let opponent = solver.policy.get(&mirror);
+            for (i, row) in (0..).zip(&solver.matrix(state)) {
+                let payoff: f64 = (0..)
+                    .zip(row)
+                    .map(|(j, p)| opponent.probability(j) * p)
+                    .sum();
+                assert!(payoff <= action.payoff() + 1e-9, "{state:?} {i}");
+            }
+            let total: f64 = action.probabilities().iter().sum();
+            assert!((total - 1.0).abs() < 1e-9, "{state:?}");
+        }
+        assert!(solver.policy.get(&State::new(5, 5, false)).payoff().abs() < 1e-12);
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+use std::io::Write;
+
+use rayon::prelude::*;
+
+use super::dp::PMFLookup;
+use super::terminal::find_optimal_terminal_action;
+use crate::{Action, Ruleset, Scalar, State};
+
+/// Computes the optimal policy for very large rulesets in less memory than
+/// `DpSolver`.
+///
+/// The in-memory `DpSolver` stores an action for all 2 × (max+1)² states and
+/// PMFs for up to max+1 dice. This solver produces the same policy while only
+/// keeping the payoffs that the remaining recursion still needs, streaming
+/// every finished action to a CSV writer instead. Its memory still grows with
+/// max², so it is not bounded.
+///
+/// # Memory Layout
+///
+/// Normal states are swept order by order (order = active + queued) from 2 ×
+/// max down to 0, exactly like `DpSolver::solve_normal_states`. A state of
+/// order `o` only depends on states of order `o+1..=o+max`, so only those
+/// diagonals (their payoffs, not their actions) are kept: up to about ¾ ×
+/// max² payoffs, midway through the sweep.
+///
+/// Terminal states are not stored at all: the normal state `(a, q)` is the
+/// only state that can stand into the terminal state `(q, a)`, so each
+/// terminal state is solved (and written) exactly once, when it is needed.
+///
+/// PMFs are truncated to totals ≤ max, since larger totals always bust. That
+/// leaves about 2 × max / (sides + 1) rows of at most max probabilities each
+/// (and as many cumulative ones).
+///
+/// For example, (50000, 6) keeps about 15 GB of payoffs and 6.5 GB of PMFs.
+///
+/// All indexing is done in `u64`/`usize`, so the state space may exceed
+/// `u32::MAX`.
+#[derive(Debug, Clone)]
+pub struct StreamSolver {
+    /// Game configuration (maximum score and die sides).
+    ruleset: Ruleset,
+    /// Truncated probability mass functions for dice rolls.
+    pmfs: PMFLookup,
+}
+
+impl StreamSolver {
+    /// Create a new streaming solver for the specified game parameters.
+    ///
+    /// PMFs are precomputed up to one die beyond the largest strategically
+    /// relevant count. Past that point the mean total exceeds the maximum
+    /// score, so adding more dice can only lower the chance of landing in
+    /// range.
+    #[must_use]
+    pub fn new(max: u32, sides: u32) -> Self {
+        let max_n = 2 * (max + sides) / (sides + 1) + 1;
+        Self {
+            ruleset: Ruleset::new(max, sides),
+            pmfs: PMFLookup::truncated(max, sides, max_n),
+        }
+    }
+    /// Let players roll at most `max_dice` dice per turn (see
+    /// `Ruleset::with_max_dice`).
+    #[must_use]
+    pub fn with_max_dice(self, max_dice: u32) -> Self {
+        Self {
+            ruleset: self.ruleset.with_max_dice(max_dice),
+            ..self
+        }
+    }
+    /// Returns the maximum score for this game configuration.
+    #[must_use]
+    pub fn max(&self) -> u32 {
+        self.ruleset.max()
+    }
+    /// Returns the number of sides on each die for this game configuration.
+    #[must_use]
+    pub fn sides(&self) -> u32 {
+        self.ruleset.sides()
+    }
+    /// Solve every state, writing the policy to a CSV file.
+    ///
+    /// Returns the optimal action in the initial state `(0, 0, normal)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written to.
+    pub fn solve(&self, path: &str) -> Result<Action, csv::Error> {
+        self.solve_to(csv::Writer::from_path(path)?)
+    }
+    /// Solve every state, writing the policy to a CSV writer.
+    ///
+    /// Creates a CSV with columns: active, queued, last, n, payoff. Rows are
+    /// written as each order finishes, so they are not sorted.
+    ///
+    /// Returns the optimal action in the initial state `(0, 0, normal)`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails.
+    pub fn solve_to<W: Write>(&self, mut writer: csv::Writer<W>) -> Result<Action, csv::Error> {
+        let max = u64::from(self.max());
+        // `diagonals[k]` holds the payoffs of order `order + 1 + k`.
+        let mut diagonals: VecDeque<Box<[f64]>> = VecDeque::new();
+        let mut initial = Action::default();
+
+        writer.serialize(("active", "queued", "last", "n", "payoff"))?;
+        for order in (0..=2 * max).rev() {
+            let places = order.min(2 * max - order);
+            let solved: Vec<(State, Action, Action)> = (0..=places)
+                .into_par_iter()
+                .map(|place| {
+                    // Calculate the player and opponent score for this order and place.
+                    let (turn, next) = if order < max {
+                        (order - place, place)
+                    } else {
+                        (max - place, (order - max) + place)
+                    };
+                    let state = State::new(turn as u32, next as u32, false);
+                    let terminal =
+                        self.find_optimal_terminal_action(state.queued(), state.active());
+                    let action =
+                        self.find_optimal_normal_action(state, terminal, order, &diagonals);
+                    (state, action, terminal)
+                })
+                .collect();
+
+            for (state, action, terminal) in &solved {
+                writer.serialize((
+                    state.active(),
+                    state.queued(),
+                    false,
+                    action.n(),
+                    action.payoff(),
+                ))?;
+                writer.serialize((
+                    state.queued(),
+                    state.active(),
+                    true,
+                    terminal.n(),
+                    terminal.payoff(),
+                ))?;
+            }
+            if order == 0 {
+                initial = solved[0].1;
+            }
+
+            // Order `order + max + 1` is out of reach for every remaining state.
+            diagonals.push_front(
+                solved
+                    .iter()
+                    .map(|(_, action, _)| action.payoff())
+                    .collect(),
+            );
+            diagonals.truncate(max as usize);
+        }
+        writer.flush()?;
+        Ok(initial)
+    }
+    /// Look up the payoff of an already solved normal state.
+    fn payoff(&self, state: State, order: u64, diagonals: &VecDeque<Box<[f64]>>) -> f64 {
+        let max = u64::from(self.max());
+        let (active, queued) = (u64::from(state.active()), u64::from(state.queued()));
+        let place = if active + queued < max {
+            queued
+        } else {
+            max - active
+        };
+        diagonals[(active + queued - order - 1) as usize][place as usize]
+    }
+    /// Find the optimal action in the normal state `state` of order `order`,
+    /// given the optimal action of the terminal state it stands into.
+    fn find_optimal_normal_action(
+        &self,
+        state: State,
+        terminal: Action,
+        order: u64,
+        diagonals: &VecDeque<Box<[f64]>>,
+    ) -> Action {
+        let headroom = self.max() - state.active();
+        let max_optimal_n = 2 * (headroom + self.sides()) / (self.sides() + 1);
+        let max_optimal_n = self
+            .ruleset
+            .max_dice()
+            .map_or(max_optimal_n, |max_dice| max_optimal_n.min(max_dice));
+
+        let mut optimal_action = Action::new(0, -terminal.payoff());
+        for dice_rolled in 1..=max_optimal_n {
+            // Every total past the headroom busts.
+            let mut payoff = self.pmfs.cdf(dice_rolled, headroom) - 1.0;
+            for dice_total in dice_rolled..=(self.sides() * dice_rolled).min(headroom) {
+                let probability = self.pmfs.lookup(dice_rolled, dice_total);
+                let next = State::new(state.queued(), state.active() + dice_total, false);
+                payoff -= probability * self.payoff(next, order, diagonals);
+            }
+            // If equal (up to rounding), the less aggressive move is taken.
+            if payoff > optimal_action.payoff() + f64::TIE_TOLERANCE {
+                optimal_action = Action::new(dice_rolled, payoff);
+            }
+        }
+        optimal_action
+    }
+    /// Find the optimal action in the terminal state `(active, queued)`.
+    ///
+    /// The search stops once busting is certain or the PMFs run out.
+    fn find_optimal_terminal_action(&self, active: u32, queued: u32) -> Action {
+        match queued.checked_sub(active) {
+            Some(gap) => find_optimal_terminal_action(
+                gap,
+                self.max() - active,
+                self.ruleset.max_dice(),
+                &self.pmfs,
+            ),
+            None => Action::new(0, 1.0),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{DpSolver, Solver};
+
+    #[test]
+    fn test_stream_matches_in_memory_solver() {
+        for max_dice in [None, Some(3)] {
+            let (ruleset, solver) = match max_dice {
+                Some(max_dice) => (
+                    Ruleset::new(30, 6).with_max_dice(max_dice),
+                    StreamSolver::new(30, 6).with_max_dice(max_dice),
+                ),
+                None => (Ruleset::new(30, 6), StreamSolver::new(30, 6)),
+            };
+            let policy = DpSolver::from_ruleset(ruleset).policy();
+            let mut buffer = Vec::new();
+            let initial = solver
+                .solve_to(csv::Writer::from_writer(&mut buffer))
+                .unwrap();
+
+            let mut reader = csv::Reader::from_reader(buffer.as_slice());
+            let mut rows = 0;
+            for row in reader.deserialize() {
+                let (active, queued, last, n, payoff): (u32, u32, bool, u32, f64) = row.unwrap();
+                let expected = policy.get(&State::new(active, queued, last));
+                assert!(
+                    (payoff - expected.payoff()).abs() < 1e-9,
+                    "({active}, {queued}, {last}): {payoff} != {}",
+                    expected.payoff()
+                );
+                assert_eq!(n, expected.n(), "({active}, {queued}, {last})");
+                rows += 1;
+            }
+            assert_eq!(rows, 2 * 31 * 31, "Every state is written exactly once");
+
+            let expected = policy.get(&State::new(0, 0, false));
+            assert!((initial.payoff() - expected.payoff()).abs() < 1e-9);
+        }
+    }
+}
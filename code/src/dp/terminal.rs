@@ -0,0 +1,189 @@
+use rayon::prelude::*;
+
+use super::dp::PMFLookup;
+use crate::{Action, Scalar, State};
+
+/// Optimal actions for terminal (final round) states, shared across maximum
+/// scores.
+///
+/// A terminal state's value only depends on how far the active player is
+/// behind (the gap, `queued - active`) and how far they are from busting (the
+/// headroom, `max - active`), not on the absolute scores. The table is indexed
+/// by those two quantities, so a table solved for some headroom answers every
+/// ruleset with the same dice and a maximum score up to that headroom.
+///
+/// States where the active player is already ahead are not stored, since
+/// standing wins them outright.
+///
+/// # Memory Layout
+///
+/// Only gaps up to the headroom are reachable (the opponent cannot be past the
+/// maximum), so `(gap, headroom)` is stored at `headroom × (headroom + 1) / 2
+/// + gap`.
+#[derive(Debug, Clone, Default)]
+pub struct TerminalTable<T: Scalar = f64> {
+    /// The optimal action for each (gap, headroom) pair.
+    actions: Box<[Action<T>]>,
+    /// The largest headroom in the table.
+    headroom: u32,
+    /// The number of sides on each die.
+    sides: u32,
+}
+
+impl<T: Scalar> TerminalTable<T> {
+    /// Solve every terminal state with a headroom up to `headroom`.
+    ///
+    /// The PMFs must cover at least `headroom + 1` dice.
+    #[must_use]
+    pub fn solve(headroom: u32, pmfs: &PMFLookup<T>) -> Self {
+        let actions: Vec<Action<T>> = (0..=headroom)
+            .into_par_iter()
+            .flat_map_iter(|h| {
+                (0..=h).map(move |gap| find_optimal_terminal_action(gap, h, None, pmfs))
+            })
+            .collect();
+
+        Self {
+            actions: actions.into_boxed_slice(),
+            headroom,
+            sides: pmfs.sides(),
+        }
+    }
+    /// Returns the largest headroom in the table.
+    #[must_use]
+    pub fn headroom(&self) -> u32 {
+        self.headroom
+    }
+    /// Returns the number of sides on each die.
+    #[must_use]
+    pub fn sides(&self) -> u32 {
+        self.sides
+    }
+    /// Returns whether the table covers every terminal state of a ruleset.
+    #[must_use]
+    pub fn covers(&self, max: u32, sides: u32) -> bool {
+        self.sides == sides && self.headroom >= max
+    }
+    /// Retrieve the optimal action for the terminal state `state` of a game
+    /// with maximum score `max`.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, state: &State, max: u32) -> Action<T> {
+        match state.queued().checked_sub(state.active()) {
+            Some(gap) => self.actions[Self::index(gap, max - state.active())],
+            // If already ahead, doing nothing wins 100% of the time.
+            None => Action::new(0, T::ONE),
+        }
+    }
+    /// Returns the index of a (gap, headroom) pair in the table.
+    #[inline]
+    fn index(gap: u32, headroom: u32) -> usize {
+        let headroom = u64::from(headroom);
+        (headroom * (headroom + 1) / 2 + u64::from(gap)) as usize
+    }
+}
+
+/// Find the optimal number of dice to roll in a terminal state that is `gap`
+/// points behind with `headroom` points left before busting.
+///
+/// Uses the mathematical property that terminal payoff functions are unimodal
+/// (single peak) to enable early termination when payoffs start decreasing.
+/// Past `headroom + 1` dice every roll busts, so the search never goes
+/// further (nor past the dice covered by the PMFs, nor past `max_dice`).
+pub(crate) fn find_optimal_terminal_action<T: Scalar>(
+    gap: u32,
+    headroom: u32,
+    max_dice: Option<u32>,
+    pmfs: &PMFLookup<T>,
+) -> Action<T> {
+    let sides = pmfs.sides();
+    let allowed = max_dice.unwrap_or(u32::MAX);
+    if sides * (gap + 1) <= headroom && gap < allowed {
+        // If there is some action A where the minimum sum > gap AND the maximum
+        // sum is ≤ headroom, then that action wins 100% of the time.
+        return Action::new(gap + 1, T::ONE);
+    }
+
+    // The search stops before trying `limit` dice.
+    let limit = (headroom + 1)
+        .min(pmfs.max_n())
+        .min(allowed.saturating_add(1));
+    let drop = T::from_f64(10e-2);
+    let tolerance = T::from_f64(T::TIE_TOLERANCE);
+    let mut optimal_action = Action::new(0, -T::ONE);
+    let mut dice_rolled = (gap / sides).min(limit); // Start at min non-zero payoff.
+
+    loop {
+        let current_payoff = calc_terminal_payoff(gap, headroom, dice_rolled, pmfs);
+        if optimal_action.payoff() - current_payoff >= drop || dice_rolled >= limit {
+            break;
+        }
+        // If equal (up to rounding), the less aggressive move is taken.
+        if current_payoff > optimal_action.payoff() + tolerance {
+            optimal_action = Action::new(dice_rolled, current_payoff);
+        }
+        dice_rolled += 1;
+    }
+
+    optimal_action
+}
+
+/// Calculate the payoff of rolling `dice_rolled` dice in a terminal state that
+/// is `gap` points behind with `headroom` points left before busting.
+///
+/// Totals in `gap + 1..=headroom` win, a total of `gap` ties, and everything
+/// else loses, so the payoff is read off the CDF in O(1).
+pub(crate) fn calc_terminal_payoff<T: Scalar>(
+    gap: u32,
+    headroom: u32,
+    dice_rolled: u32,
+    pmfs: &PMFLookup<T>,
+) -> T {
+    if dice_rolled == 0 {
+        return if gap == 0 { T::ZERO } else { -T::ONE };
+    }
+    let win = pmfs.probability_between(dice_rolled, gap + 1, headroom);
+    let tie = pmfs.probability_between(dice_rolled, gap, gap);
+    win + win + tie - T::ONE
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::DpSolver;
+
+    #[test]
+    fn test_table_matches_solver_across_max() {
+        let pmfs: PMFLookup = PMFLookup::precompute(40, 6);
+        let table = TerminalTable::solve(40, &pmfs);
+
+        for max in [10, 25, 40] {
+            let mut solver = DpSolver::new(max, 6);
+            solver.precompute_pmfs();
+            for active in 0..=max {
+                for queued in 0..=max {
+                    let state = State::new(active, queued, true);
+                    let shared = table.get(&state, max);
+                    let best = (0..=max + 1)
+                        .map(|n| solver.calc_terminal_payoff_direct(state, n))
+                        .fold(f64::NEG_INFINITY, f64::max);
+                    assert!(
+                        (shared.payoff() - best).abs() < 1e-12,
+                        "{state:?} with max {max}: {} != {best}",
+                        shared.payoff()
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_covers() {
+        let table: TerminalTable = TerminalTable::solve(20, &PMFLookup::precompute(20, 4));
+
+        assert!(table.covers(20, 4));
+        assert!(table.covers(5, 4));
+        assert!(!table.covers(21, 4));
+        assert!(!table.covers(20, 6));
+    }
+}
@@ -0,0 +1,327 @@
+//! Dependency-counted scheduling of the normal states.
+//!
+//! Solving the normal states one order at a time needs a barrier after every
+//! order, and the orders near `0` and `2 × max` hold only a handful of states.
+//! Instead, the orders are grouped into tiles, and every state of a tile runs
+//! as soon as the states it needs are done. Within the batched sweep (see
+//! `DpSolver::solve_normal_states`) the state `(a, q)` needs:
+//!
+//! + `(a + 1, q)`, which extended the window of `q` just before it,
+//! + `(q, a + k)` for `k = 1..=sides`, whose payoffs `W⁰(a + k)` it reads, and
+//! + `(q + 1, a)`, the last state to read the ring-buffer slot that landing
+//!   on `a` overwrites.
+//!
+//! With `EndRule::BothStand`, `(a, q)` also stands into the terminal state
+//! `(q, a)`, which rolls like the normal state `(q, a)`. So a task solves a
+//! state together with its mirror image (the same order), and waits for the
+//! tasks of the dependencies of both.
+//!
+//! All of them have a higher order, so a tile never deadlocks, and states in
+//! earlier tiles are already done.
+
+use std::sync::atomic::{AtomicU32, Ordering};
+use std::sync::{Mutex, OnceLock};
+
+use super::dp::{DpSolver, Window, Windows};
+use crate::{Action, EndRule, Scalar, Solver, State};
+
+/// A range of consecutive orders of normal states.
+#[derive(Debug, Clone)]
+pub(super) struct Tile {
+    /// The maximum score.
+    max: u32,
+    /// The number of sides on each die.
+    sides: u32,
+    /// The lowest order in the tile.
+    lo: u32,
+    /// The highest order in the tile.
+    hi: u32,
+    /// The index of the first state of each order, from `lo`.
+    offsets: Vec<usize>,
+}
+
+impl Tile {
+    /// Create the tile of orders `lo..=hi`.
+    pub(super) fn new(max: u32, sides: u32, lo: u32, hi: u32) -> Self {
+        let mut offsets = Vec::with_capacity((hi - lo + 2) as usize);
+        let mut len = 0;
+        for order in lo..=hi {
+            offsets.push(len);
+            len += order.min(2 * max - order) as usize + 1;
+        }
+        offsets.push(len);
+        Self {
+            max,
+            sides,
+            lo,
+            hi,
+            offsets,
+        }
+    }
+    /// Returns the lowest order in the tile.
+    pub(super) fn lo(&self) -> u32 {
+        self.lo
+    }
+    /// Returns the highest order in the tile.
+    pub(super) fn hi(&self) -> u32 {
+        self.hi
+    }
+    /// Returns the number of states in the tile.
+    pub(super) fn len(&self) -> usize {
+        self.offsets[self.offsets.len() - 1]
+    }
+    /// Iterate over the states of the tile, in index order.
+    pub(super) fn states(&self) -> impl Iterator<Item = State> + '_ {
+        (self.lo..=self.hi).flat_map(|order| {
+            let lowest = order.saturating_sub(self.max);
+            let places = order.min(2 * self.max - order);
+            (lowest..=lowest + places).map(move |next| State::new(order - next, next, false))
+        })
+    }
+    /// Returns the index of a state of the tile.
+    pub(super) fn index(&self, state: State) -> usize {
+        let order = state.active() + state.queued();
+        let lowest = order.saturating_sub(self.max);
+        self.offsets[(order - self.lo) as usize] + (state.queued() - lowest) as usize
+    }
+    /// Returns whether a state belongs to the tile (it is not yet solved).
+    fn contains(&self, state: State) -> bool {
+        (self.lo..=self.hi).contains(&(state.active() + state.queued()))
+    }
+    /// Returns the task that solves `state`: the larger-active of the state
+    /// and its mirror image.
+    pub(super) fn task(state: State) -> State {
+        match state.active() >= state.queued() {
+            true => state,
+            false => State::new(state.queued(), state.active(), false),
+        }
+    }
+    /// Returns the states solved by `task`.
+    pub(super) fn members(task: State) -> impl Iterator<Item = State> {
+        let mirror = State::new(task.queued(), task.active(), false);
+        std::iter::once(task).chain((task != mirror).then_some(mirror))
+    }
+    /// Returns the distinct tasks of the tile among `states`, other than
+    /// `task`.
+    fn tasks_among(&self, task: State, states: impl Iterator<Item = State>) -> Vec<State> {
+        let mut tasks: Vec<State> = states
+            .filter(|&s| self.contains(s))
+            .map(Self::task)
+            .filter(|&t| t != task)
+            .collect();
+        tasks.sort_unstable();
+        tasks.dedup();
+        tasks
+    }
+    /// Returns the states that `state` waits for (possibly repeated).
+    fn needs(&self, state: State) -> impl Iterator<Item = State> {
+        let (a, q, max) = (state.active(), state.queued(), self.max);
+        let next = (a < max).then(|| State::new(a + 1, q, false));
+        let landed = (1..=self.sides.min(max - a)).map(move |k| State::new(q, a + k, false));
+        let reader = (q < max).then(|| State::new(q + 1, a, false));
+        next.into_iter().chain(landed).chain(reader)
+    }
+    /// Returns the states that wait for `state` (possibly repeated), the
+    /// inverse of `needs`.
+    fn needed_by(&self, state: State) -> impl Iterator<Item = State> {
+        let (x, y) = (state.active(), state.queued());
+        let next = (x > 0).then(|| State::new(x - 1, y, false));
+        let landing = (1..=self.sides.min(y)).map(move |k| State::new(y - k, x, false));
+        let reading = (x > 0).then(|| State::new(y, x - 1, false));
+        next.into_iter().chain(landing).chain(reading)
+    }
+    /// Returns the number of tasks in the tile that `task` waits for.
+    pub(super) fn dependencies(&self, task: State) -> u32 {
+        let needs = Self::members(task).flat_map(|state| self.needs(state));
+        self.tasks_among(task, needs).len() as u32
+    }
+    /// Returns the tasks of the tile that wait for `task`.
+    pub(super) fn dependents(&self, task: State) -> Vec<State> {
+        let needed_by = Self::members(task).flat_map(|state| self.needed_by(state));
+        self.tasks_among(task, needed_by)
+    }
+}
+
+/// The shared state of one tile being solved.
+struct Wavefront<'a, T: Scalar> {
+    /// The solver, for its PMFs and terminal payoffs.
+    solver: &'a DpSolver<T>,
+    /// The tile being solved.
+    tile: &'a Tile,
+    /// The window of every opponent score.
+    rows: Vec<Mutex<Window<T>>>,
+    /// The number of unfinished dependencies of every state.
+    pending: Vec<AtomicU32>,
+    /// The solved action of every state.
+    actions: Vec<OnceLock<Action<T>>>,
+    /// The solved action of every terminal state (`EndRule::BothStand` only).
+    terminal: Vec<OnceLock<Action<T>>>,
+}
+
+impl<T: Scalar> Wavefront<'_, T> {
+    /// Solve `task`, then every dependent it is the last dependency of.
+    ///
+    /// The first ready dependent is solved on this thread, and the rest are
+    /// spawned.
+    fn run<'s>(&'s self, scope: &rayon::Scope<'s>, mut task: State) {
+        loop {
+            match self.solver.ruleset().end_rule() {
+                EndRule::BothStand => self.solve_with_terminal(task),
+                EndRule::LastTurn | EndRule::Immediate => {
+                    for state in Tile::members(task) {
+                        let action = {
+                            let mut row = self.rows[state.queued() as usize].lock().unwrap();
+                            self.solver
+                                .find_optimal_normal_action_batched(state, &mut row)
+                        };
+                        self.land(state, action);
+                    }
+                }
+            }
+
+            let mut next = None;
+            for dependent in self.tile.dependents(task) {
+                let index = self.tile.index(dependent);
+                if self.pending[index].fetch_sub(1, Ordering::AcqRel) == 1 {
+                    match next {
+                        None => next = Some(dependent),
+                        Some(_) => scope.spawn(move |scope| self.run(scope, dependent)),
+                    }
+                }
+            }
+            match next {
+                Some(dependent) => task = dependent,
+                None => break,
+            }
+        }
+    }
+    /// Solve the normal and terminal states of `task` under
+    /// `EndRule::BothStand`.
+    ///
+    /// A terminal state rolls exactly like the normal state with the same
+    /// scores, but standing ends the game. The normal states then stand into
+    /// the terminal state of their mirror image.
+    fn solve_with_terminal(&self, task: State) {
+        let solved: Vec<(State, Vec<T>, Action<T>)> = Tile::members(task)
+            .map(|state| {
+                let mut row = self.rows[state.queued() as usize].lock().unwrap();
+                row.extend(state.active(), self.solver.sides());
+                let payoffs = row.payoffs(state.active()).to_vec();
+                let terminal = State::new(state.active(), state.queued(), true);
+                let stand = self.solver.calc_terminal_payoff(terminal, 0);
+                let action = self.solver.choose_action(state, stand, &payoffs);
+                (state, payoffs, action)
+            })
+            .collect();
+
+        for (state, payoffs, terminal) in &solved {
+            let (_, _, mirror) = solved
+                .iter()
+                .find(|(other, _, _)| other.active() == state.queued())
+                .expect("a task solves the mirror image of its states");
+            let action = self.solver.choose_action(*state, -mirror.payoff(), payoffs);
+            let _ = self.terminal[self.tile.index(*state)].set(*terminal);
+            self.land(*state, action);
+        }
+    }
+    /// Record the solved action of a normal state, and its payoff as W⁰ of
+    /// the window it lands in.
+    fn land(&self, state: State, action: Action<T>) {
+        // Landing on `queued` while the opponent moves from `active` is worth
+        // the negation of the opponent's payoff, unless it is an exact win.
+        let ruleset = self.solver.ruleset();
+        let landed = match ruleset.exact_win() && state.queued() == ruleset.max() {
+            true => T::ONE,
+            false => -action.payoff(),
+        };
+        self.rows[state.active() as usize]
+            .lock()
+            .unwrap()
+            .payoffs_mut(state.queued())[0] = landed;
+        let _ = self.actions[self.tile.index(state)].set(action);
+    }
+}
+
+/// Solve every state of `tile`, updating `windows` as the order-by-order
+/// sweep would.
+///
+/// Every order above the tile must already be solved.
+pub(super) fn solve_tile<T: Scalar>(
+    solver: &DpSolver<T>,
+    tile: &Tile,
+    windows: &mut Windows<T>,
+) -> Vec<(State, Action<T>)> {
+    let wavefront = Wavefront {
+        solver,
+        tile,
+        rows: std::mem::take(&mut windows.rows)
+            .into_iter()
+            .map(Mutex::new)
+            .collect(),
+        pending: tile
+            .states()
+            .map(|state| AtomicU32::new(tile.dependencies(state)))
+            .collect(),
+        actions: (0..tile.len()).map(|_| OnceLock::new()).collect(),
+        terminal: (0..tile.len()).map(|_| OnceLock::new()).collect(),
+    };
+
+    rayon::scope(|scope| {
+        let wavefront = &wavefront;
+        for state in tile.states().filter(|&state| Tile::task(state) == state) {
+            if wavefront.pending[tile.index(state)].load(Ordering::Relaxed) == 0 {
+                scope.spawn(move |scope| wavefront.run(scope, state));
+            }
+        }
+    });
+
+    windows.rows = wavefront
+        .rows
+        .into_iter()
+        .map(|row| row.into_inner().unwrap())
+        .collect();
+    let normal = tile.states().zip(wavefront.actions).map(|(state, action)| {
+        let action = action
+            .into_inner()
+            .expect("every state of a tile is solved");
+        (state, action)
+    });
+    let terminal = tile
+        .states()
+        .zip(wavefront.terminal)
+        .filter_map(|(state, action)| {
+            let state = State::new(state.active(), state.queued(), true);
+            action.into_inner().map(|action| (state, action))
+        });
+    normal.chain(terminal).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dependencies_match_dependents() {
+        for (max, sides) in [(10, 3), (12, 6), (5, 8)] {
+            let tile = Tile::new(max, sides, 3, 2 * max - 2);
+            let tasks: Vec<State> = tile.states().filter(|&s| Tile::task(s) == s).collect();
+            let mut counts = vec![0; tile.len()];
+            for &task in &tasks {
+                for dependent in tile.dependents(task) {
+                    assert!(
+                        dependent.active() + dependent.queued() < task.active() + task.queued()
+                    );
+                    counts[tile.index(dependent)] += 1;
+                }
+            }
+            for &task in &tasks {
+                assert_eq!(
+                    counts[tile.index(task)],
+                    tile.dependencies(task),
+                    "{task:?}"
+                );
+            }
+            assert_eq!(tile.states().count(), tile.len());
+        }
+    }
+}
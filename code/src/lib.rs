@@ -21,6 +21,17 @@
 //! completed, the game ends. The player with the higher score wins; if both
 //! players have the same score, the game is declared a draw.
 //!
+//! House rules for when a stand ends the game vary, see `EndRule`, and so do
+//! those for what a bust costs, see `BustRule`. Some also let a player who
+//! lands exactly on the maximum win on the spot, see `Ruleset::with_exact_win`,
+//! a physical game only has so many dice, see `Ruleset::with_max_dice`, and
+//! a tie need not be a draw, see `TieRule`.
+//! To handicap a stronger player, each seat can have its own maximum score
+//! and dice, see `Handicap`. Many people also roll one die at a time instead
+//! of committing to a number of dice, see `PushSolver`, and in a faster
+//! variant both players choose their dice at once, see `SimultaneousSolver`.
+//! As in backgammon, the stakes can be doubled, see `CubeSolver`.
+//!
 //! ## Usage
 //!
 //! ### Playing
@@ -45,14 +56,217 @@
 //! # - `normal_payoffs.svg`
 //! cargo run --release -- solve --max 100 --sides 6 --format svg
 //! ```
+//!
+//! ### Sweeping
+//!
+//! ```sh
+//! # solves every ruleset in the grid and writes `visualize/sweep.csv`
+//! cargo run --release -- sweep --max 20..=500 --sides 2..=20
+//! ```
 
+pub mod analysis;
+pub mod compact;
 pub mod dp;
 pub mod play;
+pub mod scalar;
 pub mod solver;
 
-pub use dp::DpSolver;
+use std::fmt;
+use std::str::FromStr;
+
+pub use compact::CompactPolicy;
+pub use dp::{
+    CubeSolver, DpSolver, HandicapSolver, MultiMaxSolver, PushSolver, SimultaneousSolver,
+    StreamSolver,
+};
 pub use play::Greed;
-pub use solver::{Policy, Solver};
+pub use scalar::{DoubleDouble, Scalar};
+pub use solver::{CubePolicy, HandicapPolicy, MixedPolicy, Policy, PushPolicy, Solver};
+
+/// When a stand (rolling 0 dice) ends the game.
+///
+/// In every variant, a stand moves the game to a final-round state
+/// (`State::last`), except with `Immediate`, which never reaches one.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum EndRule {
+    /// The opponent gets exactly one more turn, then the game ends. (This is
+    /// also "everyone else gets one final turn", since there are only two
+    /// players.)
+    #[default]
+    LastTurn,
+    /// The game ends as soon as a player stands.
+    Immediate,
+    /// The game only ends when both players stand consecutively. After a
+    /// stand, the opponent may stand too (ending the game) or roll, which
+    /// resumes normal play.
+    BothStand,
+}
+
+impl EndRule {
+    /// Every end rule.
+    pub const ALL: [Self; 3] = [Self::LastTurn, Self::Immediate, Self::BothStand];
+}
+
+impl fmt::Display for EndRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::LastTurn => "last-turn",
+            Self::Immediate => "immediate",
+            Self::BothStand => "both-stand",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for EndRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.to_string() == s)
+            .ok_or_else(|| format!("unknown end rule `{s}`"))
+    }
+}
+
+/// What happens to a player whose score passes the maximum.
+///
+/// With any rule but `Lose`, a bust only costs points and passes the turn, so
+/// scores no longer only increase and a game can revisit a state. Such games
+/// are solved by value iteration (see `DpSolver::solve_normal_states`),
+/// starting from a draw everywhere, so a game that never ends counts as a
+/// draw.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum BustRule {
+    /// The player loses the game immediately.
+    #[default]
+    Lose,
+    /// The player's score is reset to 0.
+    Reset,
+    /// The player's score drops back by the amount rolled, undoing the roll.
+    Revert,
+    /// The player's score bounces back off the maximum: it becomes the
+    /// maximum minus the overshoot (but at least 0).
+    Bounce,
+}
+
+impl BustRule {
+    /// Every bust rule.
+    pub const ALL: [Self; 4] = [Self::Lose, Self::Reset, Self::Revert, Self::Bounce];
+}
+
+impl fmt::Display for BustRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Lose => "lose",
+            Self::Reset => "reset",
+            Self::Revert => "revert",
+            Self::Bounce => "bounce",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for BustRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.to_string() == s)
+            .ok_or_else(|| format!("unknown bust rule `{s}`"))
+    }
+}
+
+/// Who wins when the final scores are equal.
+///
+/// Ties are scored from the perspective of the player who moves in the final
+/// round, who never stood first. Only `SecondPlayer` depends on the seat, so
+/// it is solved by `HandicapSolver` (which knows the seat) but not by
+/// `DpSolver`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum TieRule {
+    /// The game is a draw.
+    #[default]
+    Draw,
+    /// The second player wins.
+    SecondPlayer,
+    /// The player who stood first (ending normal play) wins.
+    Stander,
+    /// Both players roll one die of their own, again until the rolls differ,
+    /// and the higher roll wins. With the same dice, this is a coin flip, so
+    /// the policy is the same as for `Draw`.
+    SuddenDeath,
+}
+
+impl TieRule {
+    /// Every tie rule.
+    pub const ALL: [Self; 4] = [
+        Self::Draw,
+        Self::SecondPlayer,
+        Self::Stander,
+        Self::SuddenDeath,
+    ];
+}
+
+impl fmt::Display for TieRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Draw => "draw",
+            Self::SecondPlayer => "second-player",
+            Self::Stander => "stander",
+            Self::SuddenDeath => "sudden-death",
+        };
+        f.write_str(name)
+    }
+}
+
+impl FromStr for TieRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|rule| rule.to_string() == s)
+            .ok_or_else(|| format!("unknown tie rule `{s}`"))
+    }
+}
+
+/// The error returned when a solver cannot solve a ruleset.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct UnsupportedRuleset {
+    /// Why the ruleset is not supported.
+    pub reason: &'static str,
+}
+
+impl fmt::Display for UnsupportedRuleset {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.reason)
+    }
+}
+
+impl std::error::Error for UnsupportedRuleset {}
+
+/// The error returned when more dice are rolled than a ruleset allows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub struct TooManyDice {
+    /// The number of dice rolled.
+    pub n: u32,
+    /// The most dice allowed per turn.
+    pub max_dice: u32,
+}
+
+impl fmt::Display for TooManyDice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "cannot roll {} dice, at most {} are allowed per turn",
+            self.n, self.max_dice
+        )
+    }
+}
+
+impl std::error::Error for TooManyDice {}
 
 /// Game configuration parameters for Greed.
 ///
@@ -65,11 +279,23 @@ pub struct Ruleset {
     max: u32,
     /// The number of sides on each die (typically 6).
     sides: u32,
+    /// The starting score of the second player (typically 0).
+    komi: u32,
+    /// When a stand ends the game.
+    end_rule: EndRule,
+    /// What happens to a player who busts.
+    bust_rule: BustRule,
+    /// Whether landing exactly on the maximum wins immediately.
+    exact_win: bool,
+    /// The most dice a player may roll in one turn (unlimited if `None`).
+    max_dice: Option<u32>,
+    /// Who wins when the final scores are equal.
+    tie_rule: TieRule,
 }
 
 impl Default for Ruleset {
     fn default() -> Self {
-        Self { max: 100, sides: 6 }
+        Self::new(100, 6)
     }
 }
 
@@ -77,7 +303,90 @@ impl Ruleset {
     /// Create a new ruleset.
     #[must_use]
     pub fn new(max: u32, sides: u32) -> Self {
-        Self { max, sides }
+        Self {
+            max,
+            sides,
+            komi: 0,
+            end_rule: EndRule::LastTurn,
+            bust_rule: BustRule::Lose,
+            exact_win: false,
+            max_dice: None,
+            tie_rule: TieRule::Draw,
+        }
+    }
+    /// Give the second player a head start of `komi` points.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `komi` exceeds the maximum score (see `try_with_komi`).
+    #[must_use]
+    pub fn with_komi(self, komi: u32) -> Self {
+        self.try_with_komi(komi)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Give the second player a head start of `komi` points, if that does not
+    /// exceed the maximum score.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `komi` exceeds the maximum score, which would
+    /// start the second player bust.
+    pub fn try_with_komi(self, komi: u32) -> Result<Self, UnsupportedRuleset> {
+        match komi <= self.max {
+            true => Ok(Self { komi, ..self }),
+            false => Err(UnsupportedRuleset {
+                reason: "the komi exceeds the maximum score",
+            }),
+        }
+    }
+    /// End the game according to `end_rule` instead of `EndRule::LastTurn`.
+    #[must_use]
+    pub fn with_end_rule(self, end_rule: EndRule) -> Self {
+        Self { end_rule, ..self }
+    }
+    /// Handle busts according to `bust_rule` instead of `BustRule::Lose`.
+    #[must_use]
+    pub fn with_bust_rule(self, bust_rule: BustRule) -> Self {
+        Self { bust_rule, ..self }
+    }
+    /// Let a player who lands exactly on the maximum win immediately (even
+    /// against an opponent on the maximum), if `exact_win`.
+    #[must_use]
+    pub fn with_exact_win(self, exact_win: bool) -> Self {
+        Self { exact_win, ..self }
+    }
+    /// Break ties according to `tie_rule` instead of `TieRule::Draw`.
+    #[must_use]
+    pub fn with_tie_rule(self, tie_rule: TieRule) -> Self {
+        Self { tie_rule, ..self }
+    }
+    /// Let players roll at most `max_dice` dice per turn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `max_dice` is 0 (see `try_with_max_dice`).
+    #[must_use]
+    pub fn with_max_dice(self, max_dice: u32) -> Self {
+        self.try_with_max_dice(max_dice)
+            .unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Let players roll at most `max_dice` dice per turn, if that is at least
+    /// one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `max_dice` is 0, which would leave players unable
+    /// to roll.
+    pub fn try_with_max_dice(self, max_dice: u32) -> Result<Self, UnsupportedRuleset> {
+        match max_dice > 0 {
+            true => Ok(Self {
+                max_dice: Some(max_dice),
+                ..self
+            }),
+            false => Err(UnsupportedRuleset {
+                reason: "players must be allowed to roll at least one die",
+            }),
+        }
     }
     /// Get the maximum score allowed before busting.
     #[must_use]
@@ -89,6 +398,269 @@ impl Ruleset {
     pub fn sides(&self) -> u32 {
         self.sides
     }
+    /// Get the starting score of the second player.
+    #[must_use]
+    pub fn komi(&self) -> u32 {
+        self.komi
+    }
+    /// Get the rule for when a stand ends the game.
+    #[must_use]
+    pub fn end_rule(&self) -> EndRule {
+        self.end_rule
+    }
+    /// Get the rule for what happens to a player who busts.
+    #[must_use]
+    pub fn bust_rule(&self) -> BustRule {
+        self.bust_rule
+    }
+    /// Get whether landing exactly on the maximum wins immediately.
+    #[must_use]
+    pub fn exact_win(&self) -> bool {
+        self.exact_win
+    }
+    /// Get the most dice a player may roll in one turn, if limited.
+    #[must_use]
+    pub fn max_dice(&self) -> Option<u32> {
+        self.max_dice
+    }
+    /// Get the rule for who wins when the final scores are equal.
+    #[must_use]
+    pub fn tie_rule(&self) -> TieRule {
+        self.tie_rule
+    }
+    /// Get the payoff of equal final scores to the player who moves in the
+    /// final round, or `None` if it depends on the seat (see `TieRule`).
+    #[must_use]
+    pub fn tie_payoff(&self) -> Option<f64> {
+        match self.tie_rule {
+            TieRule::SecondPlayer => None,
+            _ => Some(Handicap::from(*self).tie_payoff(Seat::First)),
+        }
+    }
+    /// Check that `n` dice may be rolled in one turn.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if `n` exceeds the ruleset's dice limit.
+    pub fn validate_dice(&self, n: u32) -> Result<(), TooManyDice> {
+        match self.max_dice {
+            Some(max_dice) if n > max_dice => Err(TooManyDice { n, max_dice }),
+            _ => Ok(()),
+        }
+    }
+    /// Get the score of a player on `score` after rolling a total of `total`,
+    /// or `None` if they bust and lose.
+    #[must_use]
+    pub fn score_after(&self, score: u32, total: u32) -> Option<u32> {
+        let landed = score + total;
+        if landed <= self.max {
+            return Some(landed);
+        }
+        match self.bust_rule {
+            BustRule::Lose => None,
+            BustRule::Reset => Some(0),
+            BustRule::Revert => Some(score),
+            BustRule::Bounce => Some((2 * self.max).saturating_sub(landed)),
+        }
+    }
+    /// Get the state in which the game begins.
+    #[must_use]
+    pub fn initial_state(&self) -> State {
+        State::new(0, self.komi, false)
+    }
+}
+
+/// A seat at the table: the player who moves first, or second.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum Seat {
+    /// The player who rolls first.
+    #[default]
+    First,
+    /// The player who rolls second (and starts with the komi).
+    Second,
+}
+
+impl Seat {
+    /// Both seats, in turn order.
+    pub const ALL: [Self; 2] = [Self::First, Self::Second];
+
+    /// Get the seat of the opponent.
+    #[must_use]
+    pub fn other(self) -> Self {
+        match self {
+            Self::First => Self::Second,
+            Self::Second => Self::First,
+        }
+    }
+    /// Get the position of the seat in turn order (0 or 1).
+    #[must_use]
+    pub fn index(self) -> usize {
+        match self {
+            Self::First => 0,
+            Self::Second => 1,
+        }
+    }
+}
+
+impl fmt::Display for Seat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::First => "first",
+            Self::Second => "second",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Who may double the stakes (see `CubeSolver`), from the perspective of the
+/// player to move.
+///
+/// Before any double the cube is in the middle and either player may double.
+/// After a double is taken, only the player who took it may redouble.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub enum CubeOwner {
+    /// Nobody has doubled yet.
+    #[default]
+    Centered,
+    /// The player to move owns the cube.
+    Mover,
+    /// The opponent owns the cube.
+    Opponent,
+}
+
+impl CubeOwner {
+    /// Every owner.
+    pub const ALL: [Self; 3] = [Self::Centered, Self::Mover, Self::Opponent];
+
+    /// Get the owner from the opponent's perspective, once the turn passes.
+    #[must_use]
+    pub fn other(self) -> Self {
+        match self {
+            Self::Centered => Self::Centered,
+            Self::Mover => Self::Opponent,
+            Self::Opponent => Self::Mover,
+        }
+    }
+    /// Check whether the player to move may double.
+    #[must_use]
+    pub fn can_double(self) -> bool {
+        self != Self::Opponent
+    }
+    /// Get the position of the owner in `ALL`.
+    #[must_use]
+    pub fn index(self) -> usize {
+        match self {
+            Self::Centered => 0,
+            Self::Mover => 1,
+            Self::Opponent => 2,
+        }
+    }
+}
+
+impl fmt::Display for CubeOwner {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            Self::Centered => "centered",
+            Self::Mover => "mover",
+            Self::Opponent => "opponent",
+        };
+        f.write_str(name)
+    }
+}
+
+/// A pair of rulesets, one per seat, to handicap the stronger player.
+///
+/// Each seat busts past its own maximum score, rolls its own dice and may
+/// have its own dice limit. The rest of the rules (komi, end, bust, exact-win
+/// and tie rules) apply to the whole game. At the end, the scores are
+/// compared as they are, so a lower maximum is a handicap.
+#[derive(Debug, Copy, Clone)]
+pub struct Handicap {
+    /// The ruleset of each seat, in turn order.
+    seats: [Ruleset; 2],
+}
+
+impl Handicap {
+    /// Create a game where the first player plays by `first` and the second
+    /// by `second`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the rulesets disagree on anything but the maximum score,
+    /// the die sides and the dice limit (see `try_new`).
+    #[must_use]
+    pub fn new(first: Ruleset, second: Ruleset) -> Self {
+        Self::try_new(first, second).unwrap_or_else(|error| panic!("{error}"))
+    }
+    /// Create a game where the first player plays by `first` and the second
+    /// by `second`, if they only differ in what a handicap may change.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the rulesets disagree on anything but the maximum
+    /// score, the die sides and the dice limit.
+    pub fn try_new(first: Ruleset, second: Ruleset) -> Result<Self, UnsupportedRuleset> {
+        match first.komi == second.komi
+            && first.end_rule == second.end_rule
+            && first.bust_rule == second.bust_rule
+            && first.exact_win == second.exact_win
+            && first.tie_rule == second.tie_rule
+        {
+            true => Ok(Self {
+                seats: [first, second],
+            }),
+            false => Err(UnsupportedRuleset {
+                reason: "both seats must play by the same komi, end, bust, exact-win and tie rules",
+            }),
+        }
+    }
+    /// Get the ruleset of `seat`.
+    #[must_use]
+    pub fn seat(&self, seat: Seat) -> Ruleset {
+        self.seats[seat.index()]
+    }
+    /// Get whether both seats play by the same rules.
+    #[must_use]
+    pub fn is_even(&self) -> bool {
+        let [first, second] = self.seats;
+        (first.max, first.sides, first.max_dice) == (second.max, second.sides, second.max_dice)
+    }
+    /// Get the state in which the game begins.
+    #[must_use]
+    pub fn initial_state(&self) -> State {
+        self.seats[0].initial_state()
+    }
+    /// Get the payoff of equal final scores to the player in `seat`, when it
+    /// moves in the final round (so its opponent stood first).
+    #[must_use]
+    pub fn tie_payoff(&self, seat: Seat) -> f64 {
+        match self.seats[0].tie_rule {
+            TieRule::Draw => 0.0,
+            TieRule::SecondPlayer if seat == Seat::Second => 1.0,
+            TieRule::SecondPlayer | TieRule::Stander => -1.0,
+            TieRule::SuddenDeath => {
+                // Count the pairs of rolls that each player wins; the rest
+                // are rolled again.
+                let sides = self.seat(seat).sides();
+                let other = self.seat(seat.other()).sides();
+                let (win, lose) = (1..=sides).fold((0, 0), |(win, lose), roll| {
+                    (
+                        win + (roll - 1).min(other),
+                        lose + other.saturating_sub(roll),
+                    )
+                });
+                (f64::from(win) - f64::from(lose)) / f64::from(win + lose).max(1.0)
+            }
+        }
+    }
+}
+
+impl From<Ruleset> for Handicap {
+    fn from(ruleset: Ruleset) -> Self {
+        Self {
+            seats: [ruleset; 2],
+        }
+    }
 }
 
 /// A game state in Greed, representing scores and turn information.
@@ -96,7 +668,10 @@ impl Ruleset {
 /// States are represented from the perspective of the current player:
 /// - `active`: Current player's score
 /// - `queued`: Next player's score
-/// - `last`: Whether we're in the final round (triggered when a player stands)
+/// - `last`: Whether we're in the final round (triggered when a player stands,
+///   see `EndRule`)
+/// - `seat`: The seat of the current player. Both seats play by the same
+///   rules unless there is a `Handicap`, so everything else ignores it.
 #[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
 pub struct State {
     /// The score of the player whose turn it is.
@@ -105,18 +680,26 @@ pub struct State {
     queued: u32,
     /// Whether this is the final round of the game.
     last: bool,
+    /// The seat of the player whose turn it is.
+    seat: Seat,
 }
 
 impl State {
-    /// Create a new state.
+    /// Create a new state, with the first player to move.
     #[must_use]
     pub fn new(active: u32, queued: u32, last: bool) -> Self {
         State {
             active,
             queued,
             last,
+            seat: Seat::First,
         }
     }
+    /// Let the player in `seat` move instead.
+    #[must_use]
+    pub fn with_seat(self, seat: Seat) -> Self {
+        Self { seat, ..self }
+    }
     /// Get the score of the player whose turn it is.
     #[must_use]
     pub fn active(&self) -> u32 {
@@ -131,6 +714,49 @@ impl State {
     pub fn last(&self) -> bool {
         self.last
     }
+    /// Get the seat of the player whose turn it is.
+    #[must_use]
+    pub fn seat(&self) -> Seat {
+        self.seat
+    }
+}
+
+/// A state partway through a turn of one-die-at-a-time Greed (see
+/// `PushSolver`).
+///
+/// The mover's score at the start of the turn is `state.active()`, and it has
+/// rolled `subtotal` points since. Only whether the subtotal is 0 changes the
+/// choices (a turn that rolled nothing is a stand), so the payoff depends on
+/// the current `score` and on that.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PushState {
+    /// The state at the start of the turn.
+    state: State,
+    /// The points rolled so far this turn.
+    subtotal: u32,
+}
+
+impl PushState {
+    /// Create a state `subtotal` points into the turn that began in `state`.
+    #[must_use]
+    pub fn new(state: State, subtotal: u32) -> Self {
+        Self { state, subtotal }
+    }
+    /// Get the state at the start of the turn.
+    #[must_use]
+    pub fn state(&self) -> State {
+        self.state
+    }
+    /// Get the points rolled so far this turn.
+    #[must_use]
+    pub fn subtotal(&self) -> u32 {
+        self.subtotal
+    }
+    /// Get the mover's current score, including the subtotal.
+    #[must_use]
+    pub fn score(&self) -> u32 {
+        self.state.active() + self.subtotal
+    }
 }
 
 /// An (optimal) action for a given game state, containing the number of dice to
@@ -146,19 +772,21 @@ impl State {
 /// For the `RlSolver`, the payoff represents the expected reward when following
 /// the optimal strategy from this state. Values range from -1.0 (certain
 /// victory) to 1.0 (certain win), with 0.0 representing equal chances.
+///
+/// The payoff is stored as a `Scalar` (`f64` unless otherwise specified).
 #[derive(Debug, Copy, Clone, Default)]
-pub struct Action {
+pub struct Action<T: Scalar = f64> {
     /// The number of dice to roll (0 means stand/pass).
     n: u32,
     /// The expected payoff when following optimal strategy (-1.0 to 1.0).
-    payoff: f64,
+    payoff: T,
 }
 
-impl Action {
+impl<T: Scalar> Action<T> {
     /// Create a new optimal action with a given number of dice and expected
     /// payoff.
     #[must_use]
-    pub fn new(n: u32, payoff: f64) -> Self {
+    pub fn new(n: u32, payoff: T) -> Self {
         Self { n, payoff }
     }
     /// Get the number of dice to roll.
@@ -168,7 +796,121 @@ impl Action {
     }
     /// Get the expected payoff.
     #[must_use]
-    pub fn payoff(&self) -> f64 {
+    pub fn payoff(&self) -> T {
+        self.payoff
+    }
+}
+
+/// An (optimal) action for a state of Greed with a doubling cube (see
+/// `CubeSolver`): whether to double, whether the opponent should take a
+/// double, and the number of dice to roll otherwise.
+///
+/// After a taken double the opponent owns the cube, so the dice to roll are
+/// those of the same state with `CubeOwner::Opponent`. The payoff is in units
+/// of the current stake, before any double in this state.
+#[derive(Debug, Copy, Clone, Default)]
+pub struct CubeAction<T: Scalar = f64> {
+    /// The number of dice to roll without doubling (0 means stand/pass).
+    n: u32,
+    /// Whether to double.
+    double: bool,
+    /// Whether the opponent takes a double (rather than conceding).
+    take: bool,
+    /// The expected payoff when following optimal strategy.
+    payoff: T,
+}
+
+impl<T: Scalar> CubeAction<T> {
+    /// Create a new action.
+    #[must_use]
+    pub fn new(n: u32, double: bool, take: bool, payoff: T) -> Self {
+        Self {
+            n,
+            double,
+            take,
+            payoff,
+        }
+    }
+    /// Get the number of dice to roll without doubling.
+    #[must_use]
+    pub fn n(&self) -> u32 {
+        self.n
+    }
+    /// Check whether to double.
+    #[must_use]
+    pub fn double(&self) -> bool {
+        self.double
+    }
+    /// Check whether the opponent takes a double (when the mover may offer
+    /// one).
+    #[must_use]
+    pub fn take(&self) -> bool {
+        self.take
+    }
+    /// Get the expected payoff.
+    #[must_use]
+    pub fn payoff(&self) -> T {
+        self.payoff
+    }
+}
+
+/// A mixed action for a state of simultaneous Greed (see
+/// `SimultaneousSolver`): a probability for each number of dice, and the
+/// expected payoff.
+///
+/// When both players choose at once, a player who always rolled the same
+/// number of dice could be exploited, so an equilibrium can randomize.
+#[derive(Debug, Clone, Default)]
+pub struct MixedAction<T: Scalar = f64> {
+    /// The probability of rolling each number of dice (0 means stand).
+    probabilities: Box<[T]>,
+    /// The expected payoff when both players follow the equilibrium.
+    payoff: T,
+}
+
+impl<T: Scalar> MixedAction<T> {
+    /// Create a mixed action from the probability of rolling each number of
+    /// dice and its expected payoff.
+    #[must_use]
+    pub fn new(probabilities: Vec<T>, payoff: T) -> Self {
+        Self {
+            probabilities: probabilities.into_boxed_slice(),
+            payoff,
+        }
+    }
+    /// Get the probability of rolling each number of dice, from 0.
+    #[must_use]
+    pub fn probabilities(&self) -> &[T] {
+        &self.probabilities
+    }
+    /// Get the probability of rolling `n` dice.
+    #[must_use]
+    pub fn probability(&self, n: u32) -> T {
+        self.probabilities
+            .get(n as usize)
+            .copied()
+            .unwrap_or(T::ZERO)
+    }
+    /// Iterate over the numbers of dice rolled with positive probability.
+    pub fn support(&self) -> impl Iterator<Item = u32> + '_ {
+        (0..)
+            .zip(&*self.probabilities)
+            .filter(|&(_, &p)| p > T::ZERO)
+            .map(|(n, _)| n)
+    }
+    /// Get the number of dice if the action is pure, or `None` if it
+    /// randomizes.
+    #[must_use]
+    pub fn pure(&self) -> Option<u32> {
+        let mut support = self.support();
+        match (support.next(), support.next()) {
+            (Some(n), None) => Some(n),
+            _ => None,
+        }
+    }
+    /// Get the expected payoff.
+    #[must_use]
+    pub fn payoff(&self) -> T {
         self.payoff
     }
 }
@@ -1,8 +1,17 @@
 //! Command-line interface for the Greed game and optimal policy solver.
 //!
-//! Provides two main commands:
+//! Provides eleven main commands:
 //! - `play`: Interactive game between two players
 //! - `solve`: Compute and export optimal strategies
+//! - `sweep`: Solve and summarize a grid of rulesets
+//! - `fairness`: Measure and balance the first-mover advantage
+//! - `length`: Compute the distribution of game length under optimal play
+//! - `push`: Measure what rolling one die at a time is worth
+//! - `match`: Solve first-to-k matches and compare them with single-game play
+//! - `simultaneous`: Solve the variant where both players choose at once
+//! - `cube`: Solve the variant where the stakes can be doubled
+//! - `pmf`: Report the numerical error of each PMF backend
+//! - `compare`: Count the decisions that change between numeric precisions
 //!
 //! # Examples
 //!
@@ -12,16 +21,183 @@
 //! ```
 //!
 //! ```sh
+//! # Handicap the first player with a lower maximum score
+//! cargo run -- play --max 90 --second-max 100 Alice Bob
+//! ```
+//!
+//! ```sh
 //! # Solve and visualize optimal policy
 //! cargo run -- solve --max 100 --sides 6 --format svg
 //! ```
+//!
+//! ```sh
+//! # Solve a large ruleset, saving progress every 5 minutes (rerun with
+//! # `--resume` to continue after an interruption)
+//! cargo run --release -- solve --max 3000 --sides 6 --checkpoint greed.ckpt --checkpoint-every 300
+//! ```
+//!
+//! ```sh
+//! # Summarize every ruleset in a grid
+//! cargo run --release -- sweep --max 20..=500 --sides 2..=20
+//! ```
+//!
+//! ```sh
+//! # Find the komi that makes a ruleset closest to an even game
+//! cargo run --release -- fairness --max 100 --sides 6
+//! ```
+//!
+//! ```sh
+//! # Distribution of the number of turns under optimal play
+//! cargo run --release -- length --max 100 --sides 6
+//! ```
+//!
+//! ```sh
+//! # What rolling one die at a time is worth over committing up front
+//! cargo run --release -- push --max 100 --sides 6
+//! ```
+//!
+//! ```sh
+//! # How playing for a best-of-5 match (first to 3) differs from playing
+//! # each game for itself
+//! cargo run --release -- match --max 100 --sides 6 --target 3
+//! ```
+//!
+//! ```sh
+//! # Mixed-strategy equilibrium when both players choose their dice at once
+//! cargo run --release -- simultaneous --max 100 --sides 6
+//! ```
+//!
+//! ```sh
+//! # When to double and when to take with a doubling cube
+//! cargo run --release -- cube --max 100 --sides 6
+//! ```
+//!
+//! ```sh
+//! # Compare the accuracy of the PMF backends
+//! cargo run --release -- pmf --max 1000 --sides 6
+//! ```
+//!
+//! ```sh
+//! # Count the decisions that change between f32, f64 and double-double
+//! cargo run --release -- compare --max 100 --sides 6
+//! ```
 
-use clap::{Arg, Command};
-use greed::{DpSolver, Greed, Policy, Solver};
+use std::io::IsTerminal;
+use std::ops::RangeInclusive;
+use std::time::{Duration, Instant};
 
-fn main() {
-    let play = Command::new("play")
-        .about("Start an interactive two-player game of Greed")
+use clap::builder::PossibleValuesParser;
+use clap::{Arg, ArgMatches, Command};
+use greed::analysis::{
+    GameLength, PrecisionDiff, Reachability, SweepRow, fairness, match_play, push_value, sweep,
+};
+use greed::compact::Encoding;
+use greed::dp::{CancelToken, PMF_TOLERANCE, PMFLookup, PmfBackend, Progress};
+use greed::{
+    BustRule, CompactPolicy, CubeOwner, CubeSolver, DoubleDouble, DpSolver, EndRule, Greed,
+    Handicap, Ruleset, Scalar, SimultaneousSolver, Solver, StreamSolver, TieRule,
+};
+
+/// Parse an inclusive range of the form `a..=b`, `a..b`, or `a`.
+fn parse_range(s: &str) -> Result<RangeInclusive<u32>, String> {
+    let parse = |v: &str| v.trim().parse::<u32>().map_err(|e| format!("`{v}`: {e}"));
+    let range = if let Some((lo, hi)) = s.split_once("..=") {
+        parse(lo)?..=parse(hi)?
+    } else if let Some((lo, hi)) = s.split_once("..") {
+        let hi = parse(hi)?;
+        if hi == 0 {
+            return Err(format!("`{s}` is empty"));
+        }
+        parse(lo)?..=hi - 1
+    } else {
+        let v = parse(s)?;
+        v..=v
+    };
+    if range.is_empty() {
+        return Err(format!("`{s}` is empty"));
+    }
+    Ok(range)
+}
+
+/// Parse a range of die sides as `parse_range`, rejecting dice with no sides.
+fn parse_sides_range(s: &str) -> Result<RangeInclusive<u32>, String> {
+    let range = parse_range(s)?;
+    if *range.start() < 1 {
+        return Err(format!("`{s}` includes dice with no sides"));
+    }
+    Ok(range)
+}
+
+/// The name of an output file for `ruleset`, e.g. `greed_100_6`.
+///
+/// Rulesets with other end, bust or tie rules (or exact wins, or a dice limit) are
+/// kept apart from the standard ones by a suffix naming the rule, e.g.
+/// `greed_100_6_both-stand_reset`.
+fn file_stem(prefix: &str, ruleset: Ruleset) -> String {
+    let mut stem = format!("{}_{}_{}", prefix, ruleset.max(), ruleset.sides());
+    if ruleset.end_rule() != EndRule::default() {
+        stem += &format!("_{}", ruleset.end_rule());
+    }
+    if ruleset.bust_rule() != BustRule::default() {
+        stem += &format!("_{}", ruleset.bust_rule());
+    }
+    if ruleset.exact_win() {
+        stem += "_exact";
+    }
+    if let Some(max_dice) = ruleset.max_dice() {
+        stem += &format!("_dice{max_dice}");
+    }
+    if ruleset.tie_rule() != TieRule::default() {
+        stem += &format!("_{}", ruleset.tie_rule());
+    }
+    stem
+}
+
+/// Draw a one-line progress bar for a solve on stderr.
+fn draw_progress(progress: &Progress) {
+    const WIDTH: usize = 30;
+    let filled = (progress.fraction() * WIDTH as f64).round() as usize;
+    let order = progress.order().map_or(String::new(), |order| {
+        format!(" order {}/{}", order, progress.orders())
+    });
+    let eta = progress
+        .eta()
+        .map_or(String::new(), |eta| format!(" eta {:.1?}", eta));
+    eprint!(
+        "\r\x1b[K{:>8} [{}{}]{}{}",
+        progress.phase(),
+        "#".repeat(filled),
+        " ".repeat(WIDTH - filled),
+        order,
+        eta
+    );
+}
+
+/// A rule that a subcommand lets the user set, besides the maximum score and
+/// the number of sides (see `ruleset_args`).
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Rule {
+    /// `--komi`: the starting score of the second player.
+    Komi,
+    /// `--end-rule`: when a stand ends the game, offering `both-stand` only
+    /// where games can go on until both players stand (match play rejects
+    /// it).
+    End { both_stand: bool },
+    /// `--bust`: what happens to a player who busts.
+    Bust,
+    /// `--exact-win`: whether landing exactly on the maximum score wins.
+    ExactWin,
+    /// `--tie`: who wins ties, offering `second-player` only where the
+    /// players' seats are known (solvers without seats reject it).
+    Tie { second_player: bool },
+    /// `--max-dice`: the most dice a player may roll per turn.
+    MaxDice,
+}
+
+/// Add the arguments of a `Ruleset` to `command`: the maximum score, the
+/// number of sides, and each of `rules` (parsed by `ruleset_from_matches`).
+fn ruleset_args(command: Command, rules: &[Rule]) -> Command {
+    let command = command
         .arg(
             Arg::new("max")
                 .short('m')
@@ -36,32 +212,248 @@ fn main() {
                 .short('s')
                 .long("sides")
                 .value_name("SIDES")
-                .help("Number of sides on the die")
-                .value_parser(clap::value_parser!(u32))
+                .help("Number of sides on each die")
+                .value_parser(clap::value_parser!(u32).range(1..))
                 .default_value("6"),
-        )
-        .arg(
-            Arg::new("p1")
-                .value_name("P1")
-                .help("Player 1")
-                .default_value("Alice"),
-        )
-        .arg(
-            Arg::new("p2")
-                .value_name("P2")
-                .help("Player 2")
-                .default_value("Blair"),
         );
+    rules.iter().fold(command, |command, rule| {
+        command.arg(match *rule {
+            Rule::Komi => Arg::new("komi")
+                .short('k')
+                .long("komi")
+                .value_name("KOMI")
+                .help("Starting score of the second player")
+                .value_parser(clap::value_parser!(u32))
+                .default_value("0"),
+            Rule::End { both_stand } => Arg::new("end-rule")
+                .short('e')
+                .long("end-rule")
+                .value_name("RULE")
+                .help("When a stand ends the game")
+                .value_parser(PossibleValuesParser::new(
+                    ["last-turn", "immediate", "both-stand"]
+                        .into_iter()
+                        .filter(move |&rule| both_stand || rule != "both-stand"),
+                ))
+                .default_value("last-turn"),
+            Rule::Bust => Arg::new("bust")
+                .short('b')
+                .long("bust")
+                .value_name("RULE")
+                .help("What happens to a player who busts")
+                .value_parser(["lose", "reset", "revert", "bounce"])
+                .default_value("lose"),
+            Rule::ExactWin => Arg::new("exact-win")
+                .long("exact-win")
+                .action(clap::ArgAction::SetTrue)
+                .help("Landing exactly on the maximum score wins immediately"),
+            Rule::Tie { second_player } => Arg::new("tie")
+                .short('t')
+                .long("tie")
+                .value_name("RULE")
+                .help("Who wins when the final scores are equal")
+                .value_parser(PossibleValuesParser::new(
+                    ["draw", "second-player", "stander", "sudden-death"]
+                        .into_iter()
+                        .filter(move |&rule| second_player || rule != "second-player"),
+                ))
+                .default_value("draw"),
+            Rule::MaxDice => Arg::new("max-dice")
+                .short('d')
+                .long("max-dice")
+                .value_name("DICE")
+                .help("Most dice a player may roll per turn [default: unlimited]")
+                .value_parser(clap::value_parser!(u32).range(1..)),
+        })
+    })
+}
+
+/// Parse the `Ruleset` of the arguments added by `ruleset_args`.
+fn ruleset_from_matches(args: &ArgMatches) -> Ruleset {
+    let max = *args.get_one::<u32>("max").unwrap();
+    let sides = *args.get_one::<u32>("sides").unwrap();
+    rules_from_matches(args, Ruleset::new(max, sides))
+}
+
+/// Apply the rules added by `ruleset_args` to `ruleset`, keeping its own for
+/// any rule the subcommand does not take.
+fn rules_from_matches(args: &ArgMatches, mut ruleset: Ruleset) -> Ruleset {
+    // `try_get_one` tells the rules that are not arguments of the subcommand.
+    let rule = |id: &str| args.try_get_one::<String>(id).ok().flatten();
+    if let Ok(Some(&komi)) = args.try_get_one::<u32>("komi") {
+        // The komi is bounded by the maximum score, so it is checked here.
+        ruleset = ruleset.try_with_komi(komi).unwrap_or_else(|error| {
+            let message = format!("invalid value '{komi}' for '--komi <KOMI>': {error}\n");
+            clap::Error::raw(clap::error::ErrorKind::ValueValidation, message).exit()
+        });
+    }
+    if let Some(end_rule) = rule("end-rule") {
+        ruleset = ruleset.with_end_rule(end_rule.parse().unwrap());
+    }
+    if let Some(bust_rule) = rule("bust") {
+        ruleset = ruleset.with_bust_rule(bust_rule.parse().unwrap());
+    }
+    if let Ok(Some(&exact_win)) = args.try_get_one::<bool>("exact-win") {
+        ruleset = ruleset.with_exact_win(exact_win);
+    }
+    if let Some(tie_rule) = rule("tie") {
+        ruleset = ruleset.with_tie_rule(tie_rule.parse().unwrap());
+    }
+    if let Ok(Some(&max_dice)) = args.try_get_one::<u32>("max-dice") {
+        ruleset = ruleset.with_max_dice(max_dice);
+    }
+    ruleset
+}
+
+fn main() {
+    let play = ruleset_args(
+        Command::new("play"),
+        &[
+            Rule::Komi,
+            Rule::End { both_stand: true },
+            Rule::Bust,
+            Rule::ExactWin,
+            Rule::Tie {
+                second_player: true,
+            },
+            Rule::MaxDice,
+        ],
+    )
+    .about("Start an interactive two-player game of Greed")
+    .arg(
+        Arg::new("cube")
+            .long("cube")
+            .action(clap::ArgAction::SetTrue)
+            .help("Play with a doubling cube"),
+    )
+    .arg(
+        Arg::new("second-max")
+            .long("second-max")
+            .value_name("MAX")
+            .help("Maximum score of the second player [default: --max]")
+            .value_parser(clap::value_parser!(u32)),
+    )
+    .arg(
+        Arg::new("second-sides")
+            .long("second-sides")
+            .value_name("SIDES")
+            .help("Number of sides on the second player's die [default: --sides]")
+            .value_parser(clap::value_parser!(u32).range(1..)),
+    )
+    .arg(
+        Arg::new("second-max-dice")
+            .long("second-max-dice")
+            .value_name("DICE")
+            .help("Most dice the second player may roll per turn [default: --max-dice]")
+            .value_parser(clap::value_parser!(u32).range(1..)),
+    )
+    .arg(
+        Arg::new("p1")
+            .value_name("P1")
+            .help("Player 1")
+            .default_value("Alice"),
+    )
+    .arg(
+        Arg::new("p2")
+            .value_name("P2")
+            .help("Player 2")
+            .default_value("Blair"),
+    );
+
+    let solve = ruleset_args(
+        Command::new("solve"),
+        &[
+            Rule::End { both_stand: true },
+            Rule::Bust,
+            Rule::ExactWin,
+            Rule::Tie {
+                second_player: false,
+            },
+            Rule::MaxDice,
+        ],
+    )
+    .about("Optimizes (solves) a game of Greed")
+    .arg(
+        Arg::new("method")
+            .short('M')
+            .long("method")
+            .value_name("METHOD")
+            .help("Solver method")
+            .value_parser(["dp", "rl"])
+            .default_value("dp"),
+    )
+    .arg(
+        Arg::new("format")
+            .short('f')
+            .long("format")
+            .value_parser(["stdout", "csv", "svg", "compact"])
+            .default_value("svg")
+            .help("Output format"),
+    )
+    .arg(
+        Arg::new("reach")
+            .short('r')
+            .long("reach")
+            .action(clap::ArgAction::SetTrue)
+            .help("Export the probability of visiting each state under optimal play"),
+    )
+    .arg(
+        Arg::new("pmf")
+            .long("pmf")
+            .value_name("BACKEND")
+            .help("Algorithm used to generate the dice PMFs")
+            .value_parser(["fft", "compensated", "log", "exact"])
+            .default_value("fft"),
+    )
+    .arg(
+        Arg::new("stream")
+            .long("stream")
+            .action(clap::ArgAction::SetTrue)
+            .conflicts_with_all([
+                "reach",
+                "format",
+                "method",
+                "pmf",
+                "checkpoint",
+                "end-rule",
+                "bust",
+                "exact-win",
+                "tie",
+            ])
+            .help("Stream the policy to CSV, keeping only the payoffs still needed (for very large maximum scores)"),
+    )
+    .arg(
+        Arg::new("checkpoint")
+            .long("checkpoint")
+            .value_name("PATH")
+            .help("Periodically save the progress of the normal states to PATH"),
+    )
+    .arg(
+        Arg::new("checkpoint-every")
+            .long("checkpoint-every")
+            .value_name("SECS")
+            .help("Seconds between checkpoints")
+            .value_parser(clap::value_parser!(u64))
+            .default_value("60")
+            .requires("checkpoint"),
+    )
+    .arg(
+        Arg::new("resume")
+            .long("resume")
+            .action(clap::ArgAction::SetTrue)
+            .requires("checkpoint")
+            .help("Continue from the checkpoint at --checkpoint"),
+    );
 
-    let solve = Command::new("solve")
-        .about("Optimizes (solves) a game of Greed")
+    let sweep_cmd = Command::new("sweep")
+        .about("Solves and summarizes a grid of rulesets")
         .arg(
             Arg::new("max")
                 .short('m')
                 .long("max")
                 .value_name("MAX")
-                .help("Maximum score")
-                .value_parser(clap::value_parser!(u32))
+                .help("Range of maximum scores (e.g. 20..=500)")
+                .value_parser(parse_range)
                 .default_value("100"),
         )
         .arg(
@@ -69,72 +461,457 @@ fn main() {
                 .short('s')
                 .long("sides")
                 .value_name("SIDES")
-                .help("Number of sides on each die")
-                .value_parser(clap::value_parser!(u32))
+                .help("Range of die sides (e.g. 2..=20)")
+                .value_parser(parse_sides_range)
                 .default_value("6"),
         )
         .arg(
-            Arg::new("method")
-                .short('M')
-                .long("method")
-                .value_name("METHOD")
-                .help("Solver method")
-                .value_parser(["dp", "rl"])
-                .default_value("dp"),
-        )
-        .arg(
-            Arg::new("format")
-                .short('f')
-                .long("format")
-                .value_parser(["stdout", "csv", "svg"])
-                .default_value("svg")
-                .help("Output format"),
+            Arg::new("output")
+                .short('o')
+                .long("output")
+                .value_name("OUTPUT")
+                .help("CSV file to write the summary to")
+                .default_value("visualize/sweep.csv"),
         );
 
-    let cli = Command::new("greed").subcommand(play).subcommand(solve);
+    let fairness_cmd = ruleset_args(
+        Command::new("fairness"),
+        &[
+            Rule::End { both_stand: true },
+            Rule::Bust,
+            Rule::ExactWin,
+            Rule::Tie {
+                second_player: false,
+            },
+            Rule::MaxDice,
+        ],
+    )
+    .about("Measures the first-mover advantage and balances it with a komi");
+
+    let length_cmd = ruleset_args(
+        Command::new("length"),
+        &[
+            Rule::Komi,
+            Rule::End { both_stand: true },
+            Rule::Bust,
+            Rule::ExactWin,
+            Rule::Tie {
+                second_player: false,
+            },
+            Rule::MaxDice,
+        ],
+    )
+    .about("Computes the exact distribution of game length under optimal play");
+
+    let push_cmd = ruleset_args(
+        Command::new("push"),
+        &[
+            Rule::End { both_stand: true },
+            Rule::ExactWin,
+            Rule::Tie {
+                second_player: false,
+            },
+        ],
+    )
+    .about("Measures what rolling one die at a time is worth over committing up front");
+
+    let match_cmd = ruleset_args(
+        Command::new("match"),
+        &[Rule::End { both_stand: false }, Rule::ExactWin],
+    )
+    .about("Solves first-to-k matches and compares them with single-game play")
+    .arg(
+        Arg::new("target")
+            .short('k')
+            .long("target")
+            .value_name("K")
+            .help("Games needed to win the match")
+            .value_parser(clap::value_parser!(u32).range(1..))
+            .default_value("3"),
+    );
+
+    let simultaneous_cmd = ruleset_args(Command::new("simultaneous"), &[Rule::MaxDice])
+        .about("Solves the variant where both players choose their dice at once");
+
+    let cube_cmd = ruleset_args(
+        Command::new("cube"),
+        &[
+            Rule::End { both_stand: true },
+            Rule::ExactWin,
+            Rule::MaxDice,
+        ],
+    )
+    .about("Solves Greed with a doubling cube");
+
+    let pmf_cmd = ruleset_args(Command::new("pmf"), &[])
+        .about("Reports the estimated numerical error of every PMF backend");
+
+    let compare_cmd = ruleset_args(Command::new("compare"), &[])
+        .about("Counts the decisions that change between f32, f64 and double-double solves");
+
+    let cli = Command::new("greed")
+        .subcommand(play)
+        .subcommand(solve)
+        .subcommand(sweep_cmd)
+        .subcommand(fairness_cmd)
+        .subcommand(length_cmd)
+        .subcommand(push_cmd)
+        .subcommand(match_cmd)
+        .subcommand(simultaneous_cmd)
+        .subcommand(cube_cmd)
+        .subcommand(pmf_cmd)
+        .subcommand(compare_cmd);
 
     let args = cli.get_matches();
 
     match args.subcommand() {
         Some(("play", args)) => {
-            let max = *args.get_one::<u32>("max").unwrap();
-            let sides = *args.get_one::<u32>("sides").unwrap();
             let p1 = args.get_one::<String>("p1").unwrap().as_str();
             let p2 = args.get_one::<String>("p2").unwrap().as_str();
 
-            Greed::play(max, sides, (p1, p2));
+            let first = ruleset_from_matches(args);
+            let second = Ruleset::new(
+                args.get_one::<u32>("second-max")
+                    .copied()
+                    .unwrap_or(first.max()),
+                args.get_one::<u32>("second-sides")
+                    .copied()
+                    .unwrap_or(first.sides()),
+            );
+            let second = rules_from_matches(args, second);
+            let second = match args.get_one::<u32>("second-max-dice") {
+                Some(&max_dice) => second.with_max_dice(max_dice),
+                None => second,
+            };
+            let handicap = Handicap::new(first, second);
+            match args.get_flag("cube") {
+                true => Greed::play_with_cube(handicap, (p1, p2)),
+                false => Greed::play(handicap, (p1, p2)),
+            }
         }
         Some(("solve", args)) => {
-            let max = *args.get_one::<u32>("max").unwrap();
-            let sides = *args.get_one::<u32>("sides").unwrap();
             let method = args.get_one::<String>("method").unwrap().as_str();
             let format = args.get_one::<String>("format").unwrap().as_str();
+            let reach = args.get_flag("reach");
+            let backend: PmfBackend = args.get_one::<String>("pmf").unwrap().parse().unwrap();
+            let ruleset = ruleset_from_matches(args);
+            let name = file_stem("greed", ruleset);
+
+            if args.get_flag("stream") {
+                let csv_filename = format!("visualize/{}.csv", name);
+                let solver = StreamSolver::new(ruleset.max(), ruleset.sides());
+                let solver = match ruleset.max_dice() {
+                    Some(max_dice) => solver.with_max_dice(max_dice),
+                    None => solver,
+                };
+                match solver.solve(&csv_filename) {
+                    Ok(initial) => println!(
+                        "Policy exported to {} (initial: dice #{}, payoff {})",
+                        csv_filename,
+                        initial.n(),
+                        initial.payoff()
+                    ),
+                    Err(e) => eprintln!("Failed to write CSV file: {}", e),
+                }
+                return;
+            }
 
             let policy = match method {
-                "dp" => DpSolver::new(max, sides).policy(),
+                "dp" => {
+                    let mut solver = DpSolver::from_ruleset(ruleset).with_pmf_backend(backend);
+                    if let Some(path) = args.get_one::<String>("checkpoint") {
+                        let every = *args.get_one::<u64>("checkpoint-every").unwrap();
+                        if args.get_flag("resume") {
+                            solver = match solver.resume_from(path) {
+                                Ok(solver) => solver,
+                                Err(e) => {
+                                    eprintln!("Failed to resume from {}: {}", path, e);
+                                    return;
+                                }
+                            };
+                        }
+                        solver = solver.with_checkpoints(path, Duration::from_secs(every));
+                    }
+                    let show = std::io::stderr().is_terminal();
+                    let result = solver.solve_with(
+                        |progress| {
+                            if show {
+                                draw_progress(&progress);
+                            }
+                        },
+                        &CancelToken::new(),
+                    );
+                    if show {
+                        eprintln!();
+                    }
+                    if let Err(e) = result {
+                        eprintln!("Failed to solve: {}", e);
+                        return;
+                    }
+                    solver.into_policy()
+                }
                 "rl" => todo!(),
                 _ => unreachable!("clap will panic if --method is not dp or rl"),
             };
+            let reach = reach
+                .then(|| Reachability::new(ruleset, ruleset.initial_state(), (&policy, &policy)));
 
             match format {
                 "stdout" => policy.stdout(),
                 "csv" => {
-                    let csv_filename = format!("visualize/greed_{}_{}.csv", max, sides);
-                    match policy.csv(&csv_filename) {
+                    let csv_filename = format!("visualize/{}.csv", name);
+                    let result = match &reach {
+                        Some(reach) => {
+                            policy.csv_with_reach(&csv_filename, |state| reach.get(state))
+                        }
+                        None => policy.csv(&csv_filename),
+                    };
+                    match result {
                         Ok(()) => println!("Policy exported to {}", csv_filename),
                         Err(e) => eprintln!("Failed to write CSV file: {}", e),
                     }
                 }
-                "svg" => match policy.svg() {
-                    Ok(()) => println!("SVG visualizations generated in visualize/ directory"),
-                    Err(e) => {
-                        eprintln!("Failed to generate SVG file: {}", e);
-                        eprintln!("Make sure R is installed and 'Rscript' is in your PATH");
+                "svg" => {
+                    let result = match &reach {
+                        Some(reach) => policy.svg_with_reach(|state| reach.get(state)),
+                        None => policy.svg(),
+                    };
+                    match result {
+                        Ok(()) => println!("SVG visualizations generated in visualize/ directory"),
+                        Err(e) => {
+                            eprintln!("Failed to generate SVG file: {}", e);
+                            eprintln!("Make sure R is installed and 'Rscript' is in your PATH");
+                        }
                     }
-                },
+                }
+                "compact" => {
+                    let filename = format!("visualize/{}.bin", name);
+                    let bytes = CompactPolicy::new(&policy, Encoding::RunLength).to_bytes();
+                    match std::fs::write(&filename, &bytes) {
+                        Ok(()) => println!(
+                            "Compact policy ({} bytes) exported to {}",
+                            bytes.len(),
+                            filename
+                        ),
+                        Err(e) => eprintln!("Failed to write compact policy: {}", e),
+                    }
+                }
                 _ => unreachable!(),
             }
         }
+        Some(("sweep", args)) => {
+            let maxes = args.get_one::<RangeInclusive<u32>>("max").unwrap().clone();
+            let sides = args
+                .get_one::<RangeInclusive<u32>>("sides")
+                .unwrap()
+                .clone();
+            let output = args.get_one::<String>("output").unwrap().as_str();
+
+            let rows = sweep(maxes, sides);
+            match SweepRow::csv(&rows, output) {
+                Ok(()) => println!("Summarized {} rulesets in {}", rows.len(), output),
+                Err(e) => eprintln!("Failed to write CSV file: {}", e),
+            }
+        }
+        Some(("fairness", args)) => {
+            let report = fairness(ruleset_from_matches(args));
+            let balanced = report.balanced();
+            println!("first-mover advantage: {:+.6}", report.advantage());
+            println!(
+                "balanced ruleset: max {}, sides {}, komi {} (advantage: {:+.6})",
+                balanced.max(),
+                balanced.sides(),
+                balanced.komi(),
+                report.balanced_advantage()
+            );
+        }
+        Some(("length", args)) => {
+            let ruleset = ruleset_from_matches(args);
+            let policy = DpSolver::from_ruleset(ruleset).policy();
+            let length = GameLength::new(ruleset, ruleset.initial_state(), (&policy, &policy));
+            println!(
+                "turns: mean {:.3}, median {}, 95% {}, 99% {}",
+                length.mean(),
+                length.quantile(0.5),
+                length.quantile(0.95),
+                length.quantile(0.99)
+            );
+
+            let csv_filename = format!("visualize/{}.csv", file_stem("length", ruleset));
+            match length.csv(&csv_filename) {
+                Ok(()) => println!("Distribution exported to {}", csv_filename),
+                Err(e) => eprintln!("Failed to write CSV file: {}", e),
+            }
+        }
+        Some(("push", args)) => {
+            let report = push_value(ruleset_from_matches(args));
+            let (state, gain) = report.largest_gain();
+            println!(
+                "first player: {:+.6} committing up front, {:+.6} one die at a time",
+                report.commit(),
+                report.push()
+            );
+            println!(
+                "final-round gain when not ahead: mean {:.6}, largest {:.6} at ({}, {})",
+                report.mean_gain(),
+                gain,
+                state.active(),
+                state.queued()
+            );
+            println!(
+                "mid-turn states that roll again: {:.2}%",
+                100.0 * report.rolls_on()
+            );
+        }
+        Some(("simultaneous", args)) => {
+            let ruleset = ruleset_from_matches(args);
+            let policy = SimultaneousSolver::from_ruleset(ruleset).policy();
+            let opening = policy.get(&ruleset.initial_state());
+            let support: Vec<String> = opening
+                .support()
+                .map(|n| format!("{n} dice ({:.3})", opening.probability(n)))
+                .collect();
+            println!("opening: {}", support.join(", "));
+            let mixed = policy.iter().filter(|(_, action)| action.pure().is_none());
+            println!("states with a mixed equilibrium: {}", mixed.count());
+
+            let csv_filename = format!("visualize/{}.csv", file_stem("simultaneous", ruleset));
+            match policy.csv(&csv_filename) {
+                Ok(()) => println!("Policy exported to {}", csv_filename),
+                Err(e) => eprintln!("Failed to write CSV file: {}", e),
+            }
+        }
+        Some(("cube", args)) => {
+            let ruleset = ruleset_from_matches(args);
+            let policy = CubeSolver::from_ruleset(ruleset).policy();
+            let plain = DpSolver::from_ruleset(ruleset).policy();
+            let initial = ruleset.initial_state();
+            println!(
+                "first player: {:+.6} without the cube",
+                plain.get(&initial).payoff()
+            );
+            for owner in CubeOwner::ALL {
+                let action = policy.get(&initial, owner);
+                println!(
+                    "first player, cube {}: {:+.6} (dice #{})",
+                    owner,
+                    action.payoff(),
+                    action.n()
+                );
+            }
+            for owner in CubeOwner::ALL
+                .into_iter()
+                .filter(|owner| owner.can_double())
+            {
+                let doubles: Vec<_> = policy
+                    .iter()
+                    .filter(|&(_, o, action)| o == owner && action.double())
+                    .collect();
+                let taken = doubles
+                    .iter()
+                    .filter(|(_, _, action)| action.take())
+                    .count();
+                println!(
+                    "cube {}: {} states double, {} taken, {} dropped",
+                    owner,
+                    doubles.len(),
+                    taken,
+                    doubles.len() - taken
+                );
+            }
+        }
+        Some(("match", args)) => {
+            let target = *args.get_one::<u32>("target").unwrap();
+            let report = match match_play(ruleset_from_matches(args), target) {
+                Ok(report) => report,
+                Err(e) => {
+                    eprintln!("Failed to solve the match: {}", e);
+                    return;
+                }
+            };
+            let [win, draw, lose] = report.outcomes();
+            println!(
+                "single game (starter): win {:.6}, draw {:.6}, lose {:.6}",
+                win, draw, lose
+            );
+            println!(
+                "score (starter first), match payoff vs single-game play, draw worth, decisions changed"
+            );
+            for state in report.states() {
+                println!(
+                    "{}-{}: {:+.6} vs {:+.6}, {:+.6}, {}",
+                    state.wins(),
+                    state.losses(),
+                    report.payoff(state),
+                    report.single_game_payoff(state),
+                    report.tie_payoff(state),
+                    report.changed(state)
+                );
+            }
+        }
+        Some(("pmf", args)) => {
+            let ruleset = ruleset_from_matches(args);
+            let (max, sides) = (ruleset.max(), ruleset.sides());
+
+            for backend in PmfBackend::ALL {
+                let start = Instant::now();
+                let pmfs: PMFLookup =
+                    PMFLookup::precompute_with(max, sides, backend, f64::INFINITY).unwrap();
+                let elapsed = start.elapsed();
+                let (n, error) =
+                    pmfs.errors()
+                        .iter()
+                        .enumerate()
+                        .fold(
+                            (0, 0.0),
+                            |worst, (n, &e)| if e > worst.1 { (n, e) } else { worst },
+                        );
+                println!(
+                    "{:>11}: max error {:.3e} (n = {}, tolerance {:e}) in {:.3?}",
+                    backend.to_string(),
+                    error,
+                    n,
+                    PMF_TOLERANCE,
+                    elapsed
+                );
+            }
+        }
+        Some(("compare", args)) => {
+            let ruleset = ruleset_from_matches(args);
+            let (max, sides) = (ruleset.max(), ruleset.sides());
+
+            // The compensated backend generates the PMFs in double-double too.
+            let reference = DpSolver::new(max, sides)
+                .with_pmf_backend(PmfBackend::Compensated)
+                .with_precision::<DoubleDouble>()
+                .policy();
+            let f32_policy = DpSolver::new(max, sides).with_precision::<f32>().policy();
+            let f64_policy = DpSolver::new(max, sides).policy();
+
+            println!("compared against {}:", DoubleDouble::NAME);
+            let report = |name: &str, diff: PrecisionDiff, bytes: usize| {
+                println!(
+                    "{:>4}: {} of {} decisions differ ({} normal, {} terminal), max payoff difference {:.3e}, policy {} KiB",
+                    name,
+                    diff.decisions(),
+                    diff.states(),
+                    diff.normal(),
+                    diff.terminal(),
+                    diff.max_payoff_difference(),
+                    bytes / 1024
+                );
+            };
+            report(
+                f32::NAME,
+                PrecisionDiff::new(&f32_policy, &reference),
+                f32_policy.memory_usage(),
+            );
+            report(
+                f64::NAME,
+                PrecisionDiff::new(&f64_policy, &reference),
+                f64_policy.memory_usage(),
+            );
+        }
         None => {}
         Some(_) => {
             unreachable!(
@@ -143,3 +920,20 @@ fn main() {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_range() {
+        assert_eq!(parse_range("20..=500"), Ok(20..=500));
+        assert_eq!(parse_range("2..5"), Ok(2..=4));
+        assert_eq!(parse_range("6"), Ok(6..=6));
+        for empty in ["5..=3", "3..3", "0..0"] {
+            assert!(parse_range(empty).is_err(), "{empty}");
+        }
+        assert!(parse_sides_range("0..=6").is_err());
+        assert_eq!(parse_sides_range("1..=6"), Ok(1..=6));
+    }
+}
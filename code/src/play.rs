@@ -8,7 +8,7 @@ use std::io::{Write, stdin};
 use colored::Colorize;
 use rand::{distr::Uniform, prelude::*};
 
-use crate::{Ruleset, State};
+use crate::{BustRule, EndRule, Handicap, Ruleset, Seat, State, TieRule};
 
 const WIDTH: usize = 41; // based on banner width
 const BANNER: &str = r"
@@ -22,29 +22,82 @@ const BANNER: &str = r"
 /// Interactive game runner for Greed.
 pub struct Greed {
     rng: ThreadRng,
-    ruleset: Ruleset,
+    handicap: Handicap,
     players: (String, String),
     state: State,
     turn: u32,
+    /// The player (0 or 1) who stood last outside the final round.
+    stander: Option<usize>,
+    /// The stakes and the player (0 or 1) who owns the doubling cube, if
+    /// played with one (`None` while centered).
+    cube: Option<(u32, Option<usize>)>,
 }
 
 impl Greed {
-    /// Create a new `Greed` game.
+    /// Create a new `Greed` game, with a ruleset for both players or a
+    /// `Handicap`.
     #[must_use]
-    pub fn new(max: u32, sides: u32, players: (&str, &str)) -> Self {
-        Self::banner(max, sides);
-
-        Self {
+    pub fn new(handicap: impl Into<Handicap>, players: (&str, &str)) -> Self {
+        Self::start(handicap.into(), players, None)
+    }
+    /// Create a new game and print its banner, played with a doubling cube at
+    /// `cube` (see `Greed::play_with_cube`) unless `None`.
+    fn start(
+        handicap: Handicap,
+        players: (&str, &str),
+        cube: Option<(u32, Option<usize>)>,
+    ) -> Self {
+        let greed = Self {
             rng: ThreadRng::default(),
-            ruleset: Ruleset::new(max, sides),
+            handicap,
             players: (players.0.to_string(), players.1.to_string()),
-            state: State::new(0, 0, false),
+            state: handicap.initial_state(),
             turn: 0,
-        }
+            stander: None,
+            cube,
+        };
+        greed.banner();
+        greed
     }
     /// Print the game banner.
-    fn banner(max: u32, sides: u32) {
-        let ruleset = format!("max score: {max}, sides: {sides}");
+    fn banner(&self) {
+        let handicap = self.handicap;
+        let [first, second] = Seat::ALL.map(|seat| handicap.seat(seat));
+        // A handicapped rule shows both seats' values, e.g. `100/90`.
+        let per_seat = |rule: fn(&Ruleset) -> String| match (rule(&first), rule(&second)) {
+            (first, second) if first == second => first,
+            (first, second) => format!("{first}/{second}"),
+        };
+        let max = per_seat(|ruleset| ruleset.max().to_string());
+        let sides = per_seat(|ruleset| ruleset.sides().to_string());
+        let max_dice = per_seat(|ruleset| {
+            ruleset
+                .max_dice()
+                .map_or("any".to_string(), |max_dice| max_dice.to_string())
+        });
+        let (komi, end_rule, bust_rule) = (first.komi(), first.end_rule(), first.bust_rule());
+        let mut ruleset = format!("max score: {max}, sides: {sides}");
+        if komi != 0 {
+            ruleset += &format!(", komi: {komi}");
+        }
+        if end_rule != EndRule::default() {
+            ruleset += &format!(", end: {end_rule}");
+        }
+        if bust_rule != BustRule::default() {
+            ruleset += &format!(", bust: {bust_rule}");
+        }
+        if first.tie_rule() != TieRule::default() {
+            ruleset += &format!(", ties: {}", first.tie_rule());
+        }
+        if first.exact_win() {
+            ruleset += ", exact win";
+        }
+        if first.max_dice().is_some() || second.max_dice().is_some() {
+            ruleset += &format!(", max dice: {max_dice}");
+        }
+        if self.cube.is_some() {
+            ruleset += ", cube";
+        }
         let padding = (WIDTH.saturating_sub(ruleset.len())) / 2;
 
         println!("{BANNER}");
@@ -54,23 +107,43 @@ impl Greed {
     fn game_state(&self) {
         let active = format!("{}: {}", self.active_player().white(), self.state.active());
         let queued = format!("{}: {}", self.queued_player().black(), self.state.queued());
+        let cube = match self.cube {
+            Some((value, Some(owner))) => {
+                let owner = [&self.players.0, &self.players.1][owner];
+                format!(", cube: {value} ({owner})")
+            }
+            Some((value, None)) => format!(", cube: {value}"),
+            None => String::new(),
+        };
         println!(
-            "round {round}: {active}, {queued}, last: {last}",
+            "round {round}: {active}, {queued}, last: {last}{cube}",
             round = self.turn,
             active = active.bold(),
             queued = queued.italic(),
             last = self.state.last
         );
     }
-    /// Print the game results.
-    fn results(&self) {
+    /// Print the final results header.
+    fn results_header() {
         println!();
         println!("{}", "=".repeat(WIDTH));
         println!("{pad}final results", pad = " ".repeat((WIDTH - 13) / 2));
         println!("{}", "=".repeat(WIDTH));
+    }
+    /// Print the game results, where `exact` is whether the player who just
+    /// rolled won by landing exactly on the maximum.
+    fn results(&mut self, exact: bool) {
+        Self::results_header();
 
-        let winners: &[&String] = if self.state.queued() > self.ruleset.max {
-            if self.turn % 2 == 0 {
+        // A bust or an exact win decides the game regardless of the scores.
+        let rolled = self.handicap.seat(self.state.seat().other());
+        let decided = self.state.queued() > rolled.max() || exact;
+        let tie_winner = match !decided && self.player_0() == self.player_1() {
+            true => self.tie_winner(),
+            false => None,
+        };
+        let winners: &[&String] = if decided {
+            if self.turn.is_multiple_of(2) != exact {
                 println!(
                     "{}: {}, {}: {}",
                     self.players.0,
@@ -87,7 +160,7 @@ impl Greed {
                     self.player_1().to_string().yellow()
                 );
             }
-            if self.turn % 2 == 0 {
+            if self.turn.is_multiple_of(2) != exact {
                 &[&self.players.0]
             } else {
                 &[&self.players.1]
@@ -122,20 +195,104 @@ impl Greed {
                         self.players.1,
                         self.player_1().to_string().yellow()
                     );
-                    &[&self.players.0, &self.players.1]
+                    match tie_winner {
+                        Some(0) => &[&self.players.0],
+                        Some(_) => &[&self.players.1],
+                        None => &[&self.players.0, &self.players.1],
+                    }
                 }
             }
         };
 
         if winners.len() == 1 {
-            println!("{} wins!", winners[0]);
+            match self.cube {
+                Some((value, _)) => println!("{} wins {} points!", winners[0], value),
+                None => println!("{} wins!", winners[0]),
+            }
         } else {
             println!("{} and {} tie!", winners[0], winners[1]);
         }
     }
+    /// Print the game results after the queued player dropped a double.
+    fn dropped(&self) {
+        let (value, _) = self.cube.expect("only a cube can be dropped");
+        Self::results_header();
+        println!(
+            "{}: {}, {}: {}",
+            self.players.0,
+            self.player_0(),
+            self.players.1,
+            self.player_1()
+        );
+        println!("{} drops", self.queued_player().red());
+        println!("{} wins {} points!", self.active_player(), value);
+    }
+    /// Offer the active player to double the stakes, if they may, and the
+    /// queued player to take. Returns whether the game is over (a drop).
+    ///
+    /// # Panics
+    ///
+    /// Panics if stdin input cannot be read.
+    fn offer_double(&mut self) -> bool {
+        let active = self.turn as usize % 2;
+        let Some((value, owner)) = self.cube else {
+            return false;
+        };
+        if owner.is_some_and(|owner| owner != active) {
+            return false;
+        }
+        let ask = |prompt: String| {
+            let mut input = String::new();
+            print!("{prompt} [y/N]: ");
+            std::io::stdout().flush().unwrap();
+            stdin().read_line(&mut input).unwrap();
+            matches!(input.trim().to_lowercase().as_str(), "y" | "yes")
+        };
+        if !ask(format!(
+            "{} doubles to {}?",
+            self.active_player().green(),
+            2 * value
+        )) {
+            return false;
+        }
+        if !ask(format!("{} takes?", self.queued_player().green())) {
+            self.dropped();
+            return true;
+        }
+        self.cube = Some((2 * value, Some(1 - active)));
+        false
+    }
+    /// Break a tie according to the tie rule, returning the winning player
+    /// (0 or 1), or `None` for a draw.
+    fn tie_winner(&mut self) -> Option<usize> {
+        match self.handicap.seat(Seat::First).tie_rule() {
+            TieRule::Draw => None,
+            TieRule::SecondPlayer => Some(1),
+            TieRule::Stander => self.stander,
+            TieRule::SuddenDeath => {
+                let sides = Seat::ALL.map(|seat| self.handicap.seat(seat).sides());
+                if sides == [1, 1] {
+                    return None; // the rolls never differ
+                }
+                loop {
+                    let rolls = sides
+                        .map(|sides| self.rng.sample(Uniform::new_inclusive(1, sides).unwrap()));
+                    println!(
+                        "sudden death: {} rolls {}, {} rolls {}",
+                        self.players.0, rolls[0], self.players.1, rolls[1]
+                    );
+                    match rolls[0].cmp(&rolls[1]) {
+                        Ordering::Greater => return Some(0),
+                        Ordering::Less => return Some(1),
+                        Ordering::Equal => {}
+                    }
+                }
+            }
+        }
+    }
     /// Get the active player's name.
     fn active_player(&self) -> &str {
-        if self.turn % 2 == 0 {
+        if self.turn.is_multiple_of(2) {
             &self.players.0
         } else {
             &self.players.1
@@ -143,7 +300,7 @@ impl Greed {
     }
     /// Get the queued player's name.
     fn queued_player(&self) -> &str {
-        if self.turn % 2 == 0 {
+        if self.turn.is_multiple_of(2) {
             &self.players.1
         } else {
             &self.players.0
@@ -151,7 +308,7 @@ impl Greed {
     }
     /// Get the active player's score.
     fn player_0(&self) -> u32 {
-        if self.turn % 2 == 0 {
+        if self.turn.is_multiple_of(2) {
             self.state.active()
         } else {
             self.state.queued()
@@ -159,59 +316,130 @@ impl Greed {
     }
     /// Get the queued player's score.
     fn player_1(&self) -> u32 {
-        if self.turn % 2 == 0 {
+        if self.turn.is_multiple_of(2) {
             self.state.queued()
         } else {
             self.state.active()
         }
     }
+    /// Get the ruleset of the active player.
+    fn ruleset(&self) -> Ruleset {
+        self.handicap.seat(self.state.seat())
+    }
     /// Simulate rolling `n` dice.
     fn roll(&mut self, n: u32) -> bool {
-        let sum = (0..n).fold(0, |acc, _| {
-            acc + self
-                .rng
-                .sample(Uniform::new(1, self.ruleset.sides).unwrap())
-        });
-        self.turn += 1;
-        if self.state.last {
-            self.state = State::new(self.state.queued(), self.state.active() + sum, true);
-            self.results();
-            return true;
+        let ruleset = self.ruleset();
+        let sum = roll_dice(&mut self.rng, n, ruleset.sides());
+        // A losing bust is kept past the maximum, for `results`.
+        let score = match ruleset.score_after(self.state.active(), sum) {
+            Some(score) if score != self.state.active() + sum => {
+                println!("{} busts, back to {}", self.active_player().red(), score);
+                score
+            }
+            Some(score) => score,
+            None => self.state.active() + sum,
+        };
+        if n == 0 && !self.state.last {
+            self.stander = Some(self.turn as usize % 2);
         }
-        self.state = State::new(self.state.queued(), self.state.active() + sum, n == 0);
-        if self.state.queued() > self.ruleset.max() {
-            self.results();
+        self.turn += 1;
+        let over = match ruleset.end_rule() {
+            EndRule::LastTurn => self.state.last,
+            EndRule::Immediate => n == 0,
+            EndRule::BothStand => self.state.last && n == 0,
+        };
+        let exact = ruleset.exact_win() && n > 0 && self.state.active() + sum == ruleset.max();
+        self.state =
+            State::new(self.state.queued(), score, n == 0).with_seat(self.state.seat().other());
+        if over || self.state.queued() > ruleset.max() || exact {
+            self.results(exact);
             return true;
         }
         false
     }
-    /// Start an interactive game of Greed between two players.
+    /// Start an interactive game of Greed between two players, who play by
+    /// the same ruleset or by their own (see `Handicap`).
+    ///
+    /// Players take turns entering the number of dice to roll (asking again if
+    /// their ruleset does not allow that many). The game continues until one
+    /// player busts (unless the ruleset's `BustRule` is soft), lands exactly
+    /// on the maximum (if that wins), or a stand (rolling 0 dice) ends it
+    /// according to the ruleset's `EndRule`.
     ///
-    /// Players take turns entering the number of dice to roll. The game
-    /// continues until one player busts or both players have stood (rolled
-    /// 0 dice).
+    /// # Panics
+    ///
+    /// Panics if stdin input cannot be read or parsed as a valid number.
+    pub fn play(handicap: impl Into<Handicap>, players: (&str, &str)) {
+        Greed::new(handicap, players).run();
+    }
+    /// Play the game interactively, as for `Greed::play`, with a doubling
+    /// cube: before rolling, a player may offer to double the stakes, and the
+    /// opponent either takes (and owns the cube, so only they may double
+    /// next) or concedes at the current stakes.
     ///
     /// # Panics
     ///
     /// Panics if stdin input cannot be read or parsed as a valid number.
-    pub fn play(max: u32, sides: u32, players: (&str, &str)) {
-        let mut greed = Greed::new(max, sides, players);
+    pub fn play_with_cube(handicap: impl Into<Handicap>, players: (&str, &str)) {
+        Greed::start(handicap.into(), players, Some((1, None))).run();
+    }
+    /// Play the game until it ends, offering doubles before each roll if
+    /// played with a cube.
+    fn run(mut self) {
+        let greed = &mut self;
 
+        let mut offered = false;
         loop {
             println!();
             greed.game_state();
 
+            // Offer a double once per turn, before the dice are chosen
+            if !offered && greed.offer_double() {
+                break;
+            }
+            offered = true;
+
             // Get number of dice
             let mut input = String::new();
             print!("{} rolls: ", greed.active_player().green());
             std::io::stdout().flush().unwrap();
             stdin().read_line(&mut input).unwrap();
             let n = input.trim().parse::<u32>().unwrap();
+            if let Err(e) = greed.ruleset().validate_dice(n) {
+                println!("{}", e.to_string().red());
+                continue;
+            }
 
             // Roll dice
             if greed.roll(n) {
                 break;
             }
+            offered = false;
         }
     }
 }
+
+/// Roll `n` dice with `sides` sides each, returning their total.
+fn roll_dice(rng: &mut impl Rng, n: u32, sides: u32) -> u32 {
+    let die = Uniform::new_inclusive(1, sides).unwrap();
+    (0..n).map(|_| rng.sample(die)).sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_every_face_can_be_rolled() {
+        let mut rng = StdRng::seed_from_u64(0);
+        let mut seen = [false; 7];
+        for _ in 0..1000 {
+            seen[roll_dice(&mut rng, 1, 6) as usize] = true;
+        }
+        assert_eq!(seen, [false, true, true, true, true, true, true]);
+
+        // A one-sided die always rolls 1.
+        assert_eq!(roll_dice(&mut rng, 5, 1), 5);
+    }
+}
@@ -0,0 +1,317 @@
+//! Numeric types the solver can be built with.
+//!
+//! `DpSolver`, `PMFLookup`, `Action` and `Policy` are generic over a `Scalar`
+//! (defaulting to `f64`), so the same code can produce a compact `f32` policy
+//! or a `DoubleDouble` reference policy with roughly twice the precision of
+//! `f64`.
+
+use std::fmt::{self, Debug, Display};
+use std::io::{self, Read, Write};
+use std::iter::Sum;
+use std::ops::{Add, AddAssign, Div, Mul, Neg, Sub, SubAssign};
+
+/// A real number type that payoffs and probabilities can be computed in.
+pub trait Scalar:
+    Copy
+    + Default
+    + Debug
+    + Display
+    + PartialOrd
+    + Send
+    + Sync
+    + 'static
+    + Add<Output = Self>
+    + Sub<Output = Self>
+    + Mul<Output = Self>
+    + Div<Output = Self>
+    + Neg<Output = Self>
+    + AddAssign
+    + SubAssign
+    + Sum
+{
+    /// The additive identity.
+    const ZERO: Self;
+    /// The multiplicative identity.
+    const ONE: Self;
+    /// A short name for reports.
+    const NAME: &'static str;
+    /// The largest estimated PMF error tolerated by `PMFLookup::precompute`.
+    const PMF_TOLERANCE: f64;
+    /// Payoffs closer than this are treated as equal when choosing an action,
+    /// so that rounding noise does not flip decisions between dice counts.
+    const TIE_TOLERANCE: f64;
+
+    /// Convert from an `f64` (rounding if necessary).
+    fn from_f64(value: f64) -> Self;
+    /// Convert to the nearest `f64`.
+    fn to_f64(self) -> f64;
+    /// Convert from a `u32`.
+    fn from_u32(value: u32) -> Self;
+    /// Returns the absolute value.
+    #[must_use]
+    fn abs(self) -> Self;
+    /// Write the exact value in little-endian byte order.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the writer fails.
+    fn write_le(self, writer: &mut impl Write) -> io::Result<()>;
+    /// Read a value written by `write_le`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reader fails or runs out of bytes.
+    fn read_le(reader: &mut impl Read) -> io::Result<Self>;
+}
+
+impl Scalar for f32 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const NAME: &'static str = "f32";
+    const PMF_TOLERANCE: f64 = 1e-5;
+    const TIE_TOLERANCE: f64 = 1e-6;
+
+    fn from_f64(value: f64) -> Self {
+        value as f32
+    }
+    fn to_f64(self) -> f64 {
+        f64::from(self)
+    }
+    fn from_u32(value: u32) -> Self {
+        value as f32
+    }
+    fn abs(self) -> Self {
+        f32::abs(self)
+    }
+    fn write_le(self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+    fn read_le(reader: &mut impl Read) -> io::Result<Self> {
+        let mut bytes = [0; 4];
+        reader.read_exact(&mut bytes)?;
+        Ok(f32::from_le_bytes(bytes))
+    }
+}
+
+impl Scalar for f64 {
+    const ZERO: Self = 0.0;
+    const ONE: Self = 1.0;
+    const NAME: &'static str = "f64";
+    const PMF_TOLERANCE: f64 = 1e-10;
+    const TIE_TOLERANCE: f64 = 1e-12;
+
+    fn from_f64(value: f64) -> Self {
+        value
+    }
+    fn to_f64(self) -> f64 {
+        self
+    }
+    fn from_u32(value: u32) -> Self {
+        f64::from(value)
+    }
+    fn abs(self) -> Self {
+        f64::abs(self)
+    }
+    fn write_le(self, writer: &mut impl Write) -> io::Result<()> {
+        writer.write_all(&self.to_le_bytes())
+    }
+    fn read_le(reader: &mut impl Read) -> io::Result<Self> {
+        let mut bytes = [0; 8];
+        reader.read_exact(&mut bytes)?;
+        Ok(f64::from_le_bytes(bytes))
+    }
+}
+
+/// A double-double number: an unevaluated sum `hi + lo` of two `f64`s with
+/// `|lo| ≤ ulp(hi) / 2`, giving about 106 bits of precision.
+///
+/// Only the arithmetic the solver needs is implemented. Note that PMFs are
+/// only generated in double-double precision by the compensated backend; the
+/// other backends generate `f64` PMFs and convert them.
+#[derive(Debug, Copy, Clone, Default, PartialEq)]
+pub struct DoubleDouble {
+    /// The leading part.
+    hi: f64,
+    /// The trailing error term.
+    lo: f64,
+}
+
+impl DoubleDouble {
+    /// Create a double-double from an unnormalized sum `hi + lo`.
+    #[must_use]
+    pub fn new(hi: f64, lo: f64) -> Self {
+        let (hi, lo) = two_sum(hi, lo);
+        Self { hi, lo }
+    }
+    /// Get the leading part.
+    #[must_use]
+    pub fn hi(&self) -> f64 {
+        self.hi
+    }
+    /// Get the trailing error term.
+    #[must_use]
+    pub fn lo(&self) -> f64 {
+        self.lo
+    }
+}
+
+/// Returns `(a + b, error)` exactly.
+fn two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    let bb = s - a;
+    (s, (a - (s - bb)) + (b - bb))
+}
+
+/// Returns `(a + b, error)` exactly, assuming `|a| ≥ |b|`.
+fn quick_two_sum(a: f64, b: f64) -> (f64, f64) {
+    let s = a + b;
+    (s, b - (s - a))
+}
+
+/// Returns `(a × b, error)` exactly.
+fn two_prod(a: f64, b: f64) -> (f64, f64) {
+    let p = a * b;
+    (p, a.mul_add(b, -p))
+}
+
+impl Add for DoubleDouble {
+    type Output = Self;
+
+    fn add(self, other: Self) -> Self {
+        let (s, e) = two_sum(self.hi, other.hi);
+        let (t, f) = two_sum(self.lo, other.lo);
+        let (s, e) = quick_two_sum(s, e + t);
+        let (hi, lo) = quick_two_sum(s, e + f);
+        Self { hi, lo }
+    }
+}
+
+impl Sub for DoubleDouble {
+    type Output = Self;
+
+    fn sub(self, other: Self) -> Self {
+        self + -other
+    }
+}
+
+impl Mul for DoubleDouble {
+    type Output = Self;
+
+    fn mul(self, other: Self) -> Self {
+        let (p, e) = two_prod(self.hi, other.hi);
+        let (hi, lo) = quick_two_sum(p, e + (self.hi * other.lo + self.lo * other.hi));
+        Self { hi, lo }
+    }
+}
+
+impl Div for DoubleDouble {
+    type Output = Self;
+
+    fn div(self, other: Self) -> Self {
+        // Long division, one `f64` digit at a time.
+        let q1 = self.hi / other.hi;
+        let r = self - other * Self::from_f64(q1);
+        let q2 = r.hi / other.hi;
+        let r = r - other * Self::from_f64(q2);
+        let q3 = r.hi / other.hi;
+        let (hi, lo) = quick_two_sum(q1, q2);
+        Self { hi, lo } + Self::from_f64(q3)
+    }
+}
+
+impl Neg for DoubleDouble {
+    type Output = Self;
+
+    fn neg(self) -> Self {
+        Self {
+            hi: -self.hi,
+            lo: -self.lo,
+        }
+    }
+}
+
+impl AddAssign for DoubleDouble {
+    fn add_assign(&mut self, other: Self) {
+        *self = *self + other;
+    }
+}
+
+impl SubAssign for DoubleDouble {
+    fn sub_assign(&mut self, other: Self) {
+        *self = *self - other;
+    }
+}
+
+impl Sum for DoubleDouble {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.fold(Self::ZERO, Add::add)
+    }
+}
+
+impl PartialOrd for DoubleDouble {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        match self.hi.partial_cmp(&other.hi) {
+            Some(std::cmp::Ordering::Equal) => self.lo.partial_cmp(&other.lo),
+            ordering => ordering,
+        }
+    }
+}
+
+impl Display for DoubleDouble {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Display::fmt(&self.hi, f)
+    }
+}
+
+impl Scalar for DoubleDouble {
+    const ZERO: Self = Self { hi: 0.0, lo: 0.0 };
+    const ONE: Self = Self { hi: 1.0, lo: 0.0 };
+    const NAME: &'static str = "double-double";
+    const PMF_TOLERANCE: f64 = 1e-10;
+    const TIE_TOLERANCE: f64 = 1e-24;
+
+    fn from_f64(value: f64) -> Self {
+        Self { hi: value, lo: 0.0 }
+    }
+    fn to_f64(self) -> f64 {
+        self.hi + self.lo
+    }
+    fn from_u32(value: u32) -> Self {
+        Self::from_f64(f64::from(value))
+    }
+    fn abs(self) -> Self {
+        if self.hi < 0.0 { -self } else { self }
+    }
+    fn write_le(self, writer: &mut impl Write) -> io::Result<()> {
+        self.hi.write_le(writer)?;
+        self.lo.write_le(writer)
+    }
+    fn read_le(reader: &mut impl Read) -> io::Result<Self> {
+        Ok(Self {
+            hi: f64::read_le(reader)?,
+            lo: f64::read_le(reader)?,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_double_double_beyond_f64() {
+        let third = DoubleDouble::ONE / DoubleDouble::from_u32(3);
+        // 1/3 is not representable in f64, but the error term recovers it.
+        let error = DoubleDouble::ONE - third * DoubleDouble::from_u32(3);
+        assert!(error.abs().to_f64() < 1e-30, "{error:?}");
+        assert!(third.lo() != 0.0);
+
+        // 1 + 2⁻⁶⁰ - 1 is lost in f64, but not in double-double.
+        let tiny = DoubleDouble::from_f64(2f64.powi(-60));
+        assert_eq!(
+            (DoubleDouble::ONE + tiny - DoubleDouble::ONE).to_f64(),
+            2f64.powi(-60)
+        );
+        assert!(DoubleDouble::ONE + tiny > DoubleDouble::ONE);
+    }
+}
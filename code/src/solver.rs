@@ -1,8 +1,9 @@
 //! The interface for a Greed `Solver`.
 
+use std::path::Path;
 use std::process::Command;
 
-use crate::{Action, Ruleset, State};
+use crate::{Action, CubeAction, CubeOwner, MixedAction, PushState, Ruleset, Scalar, Seat, State};
 
 /// Stores the policy for a Greed game as a lookup table.
 ///
@@ -17,44 +18,48 @@ use crate::{Action, Ruleset, State};
 ///
 /// This layout improves cache performance by keeping related states close
 /// together.
+///
+/// Payoffs are stored as a `Scalar` (`f64` unless otherwise specified); an
+/// `f32` policy takes half the memory.
 #[derive(Debug, Clone, Default)]
-pub struct Policy {
+pub struct Policy<T: Scalar = f64> {
     /// The optimal action for each state.
-    policy: Box<[Action]>,
+    policy: Box<[Action<T>]>,
     /// The maximum score.
     ///
     /// This is used for properly indexing the policy table.
     max: u32,
 }
 
-impl Policy {
+impl<T: Scalar> Policy<T> {
     /// Creates a new empty policy table for the given maximum score.
     ///
     /// Allocates space for all possible states: (max+1)² normal states +
     /// (max+1)² terminal states.
     #[must_use]
     pub fn new(max: u32) -> Self {
-        let size = ((max + 1) * (max + 1) * 2) as usize;
+        let stride = u64::from(max) + 1;
+        let size = (stride * stride * 2) as usize;
         let policy = vec![Action::default(); size].into_boxed_slice();
         Self { policy, max }
     }
     /// Returns the index of a state in the policy table.
     #[inline]
-    fn index(&self, state: &State) -> usize {
-        let stride = self.max + 1;
-        let placement = state.active() + stride * state.queued();
-        let last_offset = stride * stride * u32::from(state.last());
+    pub(crate) fn index(&self, state: &State) -> usize {
+        let stride = u64::from(self.max) + 1;
+        let placement = u64::from(state.active()) + stride * u64::from(state.queued());
+        let last_offset = stride * stride * u64::from(state.last());
         (placement + last_offset) as usize
     }
     /// Retrieve the optimal action for a given game state.
     #[must_use]
     #[inline]
-    pub fn get(&self, state: &State) -> Action {
+    pub fn get(&self, state: &State) -> Action<T> {
         self.policy[self.index(state)]
     }
     /// Store the optimal action for a given game state.
     #[inline]
-    pub fn set(&mut self, state: &State, action: Action) {
+    pub fn set(&mut self, state: &State, action: Action<T>) {
         let idx = self.index(state);
         self.policy[idx] = action;
     }
@@ -62,36 +67,47 @@ impl Policy {
     ///
     /// Yields tuples of (state, optimal_action) for every state in the game.
     /// Useful for analysis, visualization, and policy export.
-    ///
-    /// # Panics
-    ///
-    /// Panics if the state space is too large to fit in a `u32`.
-    pub fn iter(&self) -> impl Iterator<Item = (State, Action)> + '_ {
-        self.policy.iter().enumerate().map(|(placement, action)| {
-            let placement = u32::try_from(placement).expect("state space too big");
-            let last_offset = (self.max + 1) * (self.max + 1);
-            let (active, queued, last) = if placement >= last_offset {
-                let adjusted_placement = placement - last_offset;
-                let active = adjusted_placement % (self.max + 1);
-                let queued = adjusted_placement / (self.max + 1);
-                (active, queued, true)
-            } else {
-                let active = placement % (self.max + 1);
-                let queued = placement / (self.max + 1);
-                (active, queued, false)
-            };
-            (State::new(active, queued, last), *action)
-        })
+    pub fn iter(&self) -> impl Iterator<Item = (State, Action<T>)> + '_ {
+        self.policy
+            .iter()
+            .enumerate()
+            .map(|(placement, action)| (self.state(placement), *action))
+    }
+    /// Returns the maximum score.
+    #[must_use]
+    pub(crate) fn max(&self) -> u32 {
+        self.max
+    }
+    /// Returns the number of states in the policy table.
+    #[must_use]
+    pub(crate) fn len(&self) -> usize {
+        self.policy.len()
+    }
+    /// Returns the state stored at an index of the policy table.
+    pub(crate) fn state(&self, placement: usize) -> State {
+        let stride = u64::from(self.max) + 1;
+        let placement = placement as u64;
+        let last = placement >= stride * stride;
+        let placement = placement % (stride * stride);
+        // Both scores are at most `max`, so they fit in a `u32`.
+        let active = (placement % stride) as u32;
+        let queued = (placement / stride) as u32;
+        State::new(active, queued, last)
     }
 }
 
-impl Policy {
+impl<T: Scalar> Policy<T> {
+    /// Returns the memory used by the policy table in bytes.
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        std::mem::size_of_val(&*self.policy)
+    }
     /// Output the complete policy in human-readable format to stdout.
     ///
     /// Prints all state-action pairs sorted by state type and scores, useful
     /// for analysis and debugging.
     pub fn stdout(&self) {
-        let mut state_action_pairs: Vec<(State, Action)> = self.iter().collect();
+        let mut state_action_pairs: Vec<(State, Action<T>)> = self.iter().collect();
         state_action_pairs.sort_by_key(|(state, _)| (state.last(), state.active(), state.queued()));
 
         let (terminal_states, normal_states): (Vec<_>, Vec<_>) = state_action_pairs
@@ -128,18 +144,50 @@ impl Policy {
     ///
     /// Returns an error if the file cannot be created or written to.
     pub fn csv(&self, path: &str) -> Result<(), csv::Error> {
+        self.write_csv(Path::new(path), None)
+    }
+    /// Export the policy together with state visit probabilities, given by
+    /// `reach` (e.g. `Reachability::get`).
+    ///
+    /// Creates a CSV with columns: active, queued, last, n, payoff, reach
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written to.
+    pub fn csv_with_reach(
+        &self,
+        path: &str,
+        reach: impl Fn(&State) -> f64,
+    ) -> Result<(), csv::Error> {
+        self.write_csv(Path::new(path), Some(&reach))
+    }
+    /// Write the policy (and optionally visit probabilities) as CSV.
+    fn write_csv(
+        &self,
+        path: &Path,
+        reach: Option<&dyn Fn(&State) -> f64>,
+    ) -> Result<(), csv::Error> {
         let mut writer = csv::Writer::from_path(path)?;
 
         // Write headers
-        writer.serialize(("active", "queued", "last", "n", "payoff"))?;
+        match reach {
+            None => writer.serialize(("active", "queued", "last", "n", "payoff"))?,
+            Some(_) => writer.serialize(("active", "queued", "last", "n", "payoff", "reach"))?,
+        }
         for (state, action) in self.iter() {
-            writer.serialize((
+            let row = (
                 state.active(),
                 state.queued(),
                 state.last(),
                 action.n(),
-                action.payoff(),
-            ))?;
+                action.payoff().to_f64(),
+            );
+            match reach {
+                None => writer.serialize(row)?,
+                Some(reach) => {
+                    writer.serialize((row.0, row.1, row.2, row.3, row.4, reach(&state)))?;
+                }
+            }
         }
         writer.flush()?;
         Ok(())
@@ -155,23 +203,32 @@ impl Policy {
     /// Returns an error if R is not available, the script fails, or file I/O
     /// fails.
     pub fn svg(&self) -> Result<(), Box<dyn std::error::Error>> {
+        self.write_svg(None)
+    }
+    /// Generate SVG visualizations that mask states which are never visited,
+    /// given their visit probabilities by `reach` (e.g. `Reachability::get`).
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if R is not available, the script fails, or file I/O
+    /// fails.
+    pub fn svg_with_reach(
+        &self,
+        reach: impl Fn(&State) -> f64,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        self.write_svg(Some(&reach))
+    }
+    /// Render the policy (and optionally visit probabilities) with R.
+    fn write_svg(
+        &self,
+        reach: Option<&dyn Fn(&State) -> f64>,
+    ) -> Result<(), Box<dyn std::error::Error>> {
         // Create temporary CSV file
         let temp_file = tempfile::NamedTempFile::new()?;
         let temp_path = temp_file.path();
 
         // Write CSV data to temporary file
-        let mut writer = csv::Writer::from_path(temp_path)?;
-        writer.serialize(("active", "queued", "last", "n", "payoff"))?;
-        for (state, action) in self.iter() {
-            writer.serialize((
-                state.active(),
-                state.queued(),
-                state.last(),
-                action.n(),
-                action.payoff(),
-            ))?;
-        }
-        writer.flush()?;
+        self.write_csv(temp_path, reach)?;
 
         let output = Command::new("Rscript")
             .arg("optimal_policies.R")
@@ -195,13 +252,251 @@ impl Policy {
     }
 }
 
+/// Stores the policy of a handicapped game (see `Handicap`), one table per
+/// seat.
+///
+/// A seat's scores are bounded by its own maximum and its opponent's, so each
+/// table is laid out like a `Policy` for the larger of the two maximum scores,
+/// and the states that cannot occur are left at the default action.
+#[derive(Debug, Clone, Default)]
+pub struct HandicapPolicy<T: Scalar = f64> {
+    /// The policy of each seat, in turn order.
+    seats: [Policy<T>; 2],
+}
+
+impl<T: Scalar> HandicapPolicy<T> {
+    /// Creates a new empty policy for the larger maximum score `max` of the
+    /// two seats.
+    #[must_use]
+    pub fn new(max: u32) -> Self {
+        Self {
+            seats: [Policy::new(max), Policy::new(max)],
+        }
+    }
+    /// Retrieve the optimal action for a given game state, for the player in
+    /// its seat.
+    #[must_use]
+    #[inline]
+    pub fn get(&self, state: &State) -> Action<T> {
+        self.seats[state.seat().index()].get(state)
+    }
+    /// Store the optimal action for a given game state, for the player in
+    /// its seat.
+    #[inline]
+    pub fn set(&mut self, state: &State, action: Action<T>) {
+        self.seats[state.seat().index()].set(state, action);
+    }
+    /// Returns the policy of the player in `seat`.
+    #[must_use]
+    pub fn seat(&self, seat: Seat) -> &Policy<T> {
+        &self.seats[seat.index()]
+    }
+    /// Returns the memory used by both policy tables in bytes.
+    #[must_use]
+    pub fn memory_usage(&self) -> usize {
+        self.seats.iter().map(Policy::memory_usage).sum()
+    }
+}
+
+/// Stores the policy of one-die-at-a-time Greed (see `PushSolver`).
+///
+/// Every action is either to roll one more die (`n = 1`) or to stop
+/// (`n = 0`). Turns that have not rolled yet are looked up in `turn_start`,
+/// where stopping is a stand. Turns that have are looked up in `mid_turn` by
+/// the current score, where stopping banks the subtotal and passes the turn
+/// (or, in the final round, ends the game).
+#[derive(Debug, Clone, Default)]
+pub struct PushPolicy<T: Scalar = f64> {
+    /// The actions at the start of a turn, laid out like a `Policy`.
+    turn_start: Policy<T>,
+    /// The actions after rolling at least once, by current score.
+    mid_turn: Policy<T>,
+}
+
+impl<T: Scalar> PushPolicy<T> {
+    /// Creates a new empty policy for the given maximum score.
+    #[must_use]
+    pub fn new(max: u32) -> Self {
+        Self {
+            turn_start: Policy::new(max),
+            mid_turn: Policy::new(max),
+        }
+    }
+    /// Retrieve the optimal action partway through a turn.
+    #[must_use]
+    pub fn get(&self, state: &PushState) -> Action<T> {
+        match state.subtotal() {
+            0 => self.turn_start.get(&state.state()),
+            _ => self.mid_turn.get(&Self::current(state)),
+        }
+    }
+    /// Store the optimal action partway through a turn.
+    pub fn set(&mut self, state: &PushState, action: Action<T>) {
+        match state.subtotal() {
+            0 => self.turn_start.set(&state.state(), action),
+            _ => self.mid_turn.set(&Self::current(state), action),
+        }
+    }
+    /// Returns the actions at the start of a turn.
+    #[must_use]
+    pub fn turn_start(&self) -> &Policy<T> {
+        &self.turn_start
+    }
+    /// Returns the actions after rolling at least once, by current score.
+    #[must_use]
+    pub fn mid_turn(&self) -> &Policy<T> {
+        &self.mid_turn
+    }
+    /// Returns the actions at the start of a turn, for solving.
+    pub(crate) fn turn_start_mut(&mut self) -> &mut Policy<T> {
+        &mut self.turn_start
+    }
+    /// Returns the actions after rolling at least once, for solving.
+    pub(crate) fn mid_turn_mut(&mut self) -> &mut Policy<T> {
+        &mut self.mid_turn
+    }
+    /// Returns the state with the mover on its current score.
+    fn current(state: &PushState) -> State {
+        let start = state.state();
+        State::new(state.score(), start.queued(), start.last())
+    }
+}
+
+/// Stores the policy of Greed with a doubling cube (see `CubeSolver`), one
+/// table per `CubeOwner`, each laid out like a `Policy`.
+#[derive(Debug, Clone, Default)]
+pub struct CubePolicy<T: Scalar = f64> {
+    /// The optimal action for each owner and state.
+    policy: Box<[CubeAction<T>]>,
+    /// The maximum score.
+    max: u32,
+}
+
+impl<T: Scalar> CubePolicy<T> {
+    /// Creates a new empty policy for the given maximum score.
+    #[must_use]
+    pub fn new(max: u32) -> Self {
+        let stride = u64::from(max) + 1;
+        let size = (stride * stride * 2) as usize * CubeOwner::ALL.len();
+        let policy = vec![CubeAction::default(); size].into_boxed_slice();
+        Self { policy, max }
+    }
+    /// Returns the index of a state and owner in the policy table.
+    fn index(&self, state: &State, owner: CubeOwner) -> usize {
+        let stride = u64::from(self.max) + 1;
+        let placement = u64::from(state.active()) + stride * u64::from(state.queued());
+        let last_offset = stride * stride * u64::from(state.last());
+        let owner_offset = stride * stride * 2 * owner.index() as u64;
+        (placement + last_offset + owner_offset) as usize
+    }
+    /// Retrieve the optimal action for a given state and cube owner.
+    #[must_use]
+    pub fn get(&self, state: &State, owner: CubeOwner) -> CubeAction<T> {
+        self.policy[self.index(state, owner)]
+    }
+    /// Store the optimal action for a given state and cube owner.
+    pub fn set(&mut self, state: &State, owner: CubeOwner, action: CubeAction<T>) {
+        let idx = self.index(state, owner);
+        self.policy[idx] = action;
+    }
+    /// Iterate over every state, cube owner and optimal action.
+    pub fn iter(&self) -> impl Iterator<Item = (State, CubeOwner, CubeAction<T>)> + '_ {
+        let stride = u64::from(self.max) + 1;
+        (0..)
+            .zip(&*self.policy)
+            .map(move |(placement, action): (u64, _)| {
+                let owner = CubeOwner::ALL[(placement / (stride * stride * 2)) as usize];
+                let last = placement / (stride * stride) % 2 == 1;
+                let placement = placement % (stride * stride);
+                // Both scores are at most `max`, so they fit in a `u32`.
+                let state = State::new(
+                    (placement % stride) as u32,
+                    (placement / stride) as u32,
+                    last,
+                );
+                (state, owner, *action)
+            })
+    }
+}
+
+/// Stores the equilibrium of simultaneous Greed (see `SimultaneousSolver`).
+///
+/// A state is `(own score, opponent's score)`, so it is looked up with
+/// `State::new(own, opponent, false)`, and the game is symmetric, so both
+/// players use the same table.
+#[derive(Debug, Clone, Default)]
+pub struct MixedPolicy<T: Scalar = f64> {
+    /// The mixed action for each state, at `own + (max+1) * opponent`.
+    policy: Box<[MixedAction<T>]>,
+    /// The maximum score.
+    max: u32,
+}
+
+impl<T: Scalar> MixedPolicy<T> {
+    /// Creates a new empty policy for the given maximum score.
+    #[must_use]
+    pub fn new(max: u32) -> Self {
+        let stride = max as usize + 1;
+        let policy = vec![MixedAction::default(); stride * stride].into_boxed_slice();
+        Self { policy, max }
+    }
+    /// Returns the index of a state in the policy table.
+    fn index(&self, state: &State) -> usize {
+        let stride = self.max as usize + 1;
+        state.active() as usize + stride * state.queued() as usize
+    }
+    /// Retrieve the mixed action for a given state.
+    #[must_use]
+    pub fn get(&self, state: &State) -> &MixedAction<T> {
+        &self.policy[self.index(state)]
+    }
+    /// Store the mixed action for a given state.
+    pub fn set(&mut self, state: &State, action: MixedAction<T>) {
+        let idx = self.index(state);
+        self.policy[idx] = action;
+    }
+    /// Iterate over every state and its mixed action.
+    pub fn iter(&self) -> impl Iterator<Item = (State, &MixedAction<T>)> + '_ {
+        let stride = self.max + 1;
+        (0..).zip(&*self.policy).map(move |(placement, action)| {
+            let state = State::new(placement % stride, placement / stride, false);
+            (state, action)
+        })
+    }
+    /// Export the policy to a CSV file.
+    ///
+    /// Creates a CSV with columns: own, opponent, n, probability, payoff, with
+    /// a row for every number of dice rolled with positive probability.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created or written to.
+    pub fn csv(&self, path: &str) -> Result<(), csv::Error> {
+        let mut writer = csv::Writer::from_path(path)?;
+        writer.serialize(("own", "opponent", "n", "probability", "payoff"))?;
+        for (state, action) in self.iter() {
+            for n in action.support() {
+                writer.serialize((
+                    state.active(),
+                    state.queued(),
+                    n,
+                    action.probability(n).to_f64(),
+                    action.payoff().to_f64(),
+                ))?;
+            }
+        }
+        writer.flush()?;
+        Ok(())
+    }
+}
+
 /// A solver for the game of Greed.
 ///
 /// The solver will find some "optimal" policy for greed with the given ruleset.
 /// The term "optimal" is defined in context of the solver's design.
-pub trait Solver {
+pub trait Solver<T: Scalar = f64> {
     fn ruleset(&self) -> Ruleset;
-    fn policy(&mut self) -> Policy;
+    fn policy(&mut self) -> Policy<T>;
 }
 
 pub enum OutputFormat {